@@ -0,0 +1,96 @@
+use std::io;
+use std::io::Read;
+use crate::{ALPHABET_SIZE, BUFFER_SIZE, TotalOccurenceTable};
+
+// byte statistics which can be fed incrementally and merged with other accumulators
+
+#[derive(Clone)]
+#[derive(Debug)]
+pub struct EntropyAccumulator {
+    occurences: TotalOccurenceTable,
+    total_length: usize,
+}
+
+impl EntropyAccumulator {
+    pub fn new() -> Self {
+        Self {
+            occurences: [0; ALPHABET_SIZE],
+            total_length: 0,
+        }
+    }
+
+    // count the bytes of the slice
+
+    pub fn update(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.occurences[byte as usize] += 1;
+        }
+        self.total_length += bytes.len();
+    }
+
+    // uncount the bytes of the slice, which must have been previously counted
+
+    pub fn remove(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.occurences[byte as usize] -= 1;
+        }
+        self.total_length -= bytes.len();
+    }
+
+    // count the bytes of the reader until it's exhausted; returns the number of bytes read
+
+    pub fn update_from_reader<R: Read>(&mut self, reader: &mut R) -> io::Result<usize> {
+        let mut buf: [u8; BUFFER_SIZE] = [0; BUFFER_SIZE];
+        let mut bytes_read = 0;
+
+        loop {
+            let n = match reader.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+
+            self.update(&buf[..n]);
+            bytes_read += n;
+        }
+
+        return Ok(bytes_read);
+    }
+
+    // add the statistics gathered by another accumulator, e.g. from a different chunk or thread
+
+    pub fn merge(&mut self, other: &EntropyAccumulator) {
+        for letter in 0..ALPHABET_SIZE {
+            self.occurences[letter] += other.occurences[letter];
+        }
+        self.total_length += other.total_length;
+    }
+
+    pub fn occurences(&self) -> &[usize; ALPHABET_SIZE] {
+        return &self.occurences;
+    }
+
+    pub fn total_length(&self) -> usize {
+        return self.total_length;
+    }
+
+    pub fn entropy(&self) -> f64 {
+        let mut entropy = 0.0;
+
+        for letter in 0..ALPHABET_SIZE {
+            let occurence_pbb: f64 = self.occurences[letter] as f64 / self.total_length as f64;
+            if occurence_pbb > 0.0 {
+                entropy += -occurence_pbb * f64::log2(occurence_pbb);
+            }
+        }
+
+        return entropy;
+    }
+}
+
+impl Default for EntropyAccumulator {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
@@ -1,128 +1,113 @@
-use std::fs::File;
-use std::io::Read;
-
-//type ConditionalOccurenceTable = [[usize; ALPHABET_SIZE]; ALPHABET_SIZE];
+use std::collections::HashMap;
+use std::io::{ErrorKind, Read};
+use crate::accumulator::EntropyAccumulator;
+
+pub mod accumulator;
+pub mod measures;
+pub mod profile;
+pub mod stats;
+pub mod symbols;
+
+type Context = Vec<u8>;
+type ConditionalOccurenceTable = HashMap<Context, HashMap<u8, usize>>;
 type TotalOccurenceTable = [usize; ALPHABET_SIZE];
 
 const ALPHABET_SIZE: usize = 256;
 const BUFFER_SIZE: usize = 32;
 
-/*
-// compute conditional occurences for each byte
-fn count_cond_occurences(file: &mut File) -> ConditionalOccurenceTable {
-    let mut cond_occ = [[0; ALPHABET_SIZE]; ALPHABET_SIZE];
-    let mut buffer: [u8; BUFFER_SIZE] = Default::default();
-
-    let mut prev: usize = 0;
-    loop {
-        let n = file.read(&mut buffer).expect("invalid file read");
+// compute the occurences of each byte following every context of {order} previous bytes;
+// the bytes before the beginning of the file are assumed to be zeros
 
-        for curr in buffer {
-            cond_occ[curr as usize][prev] += 1;
-            prev = curr as usize;
-        }
+fn count_cond_occurences<R: Read>(reader: &mut R, order: usize) -> ConditionalOccurenceTable {
+    let mut cond_occ: ConditionalOccurenceTable = HashMap::new();
+    let mut buf: [u8; BUFFER_SIZE] = [0; BUFFER_SIZE];
 
-        if n < BUFFER_SIZE {
-            break;
+    let mut context: Context = vec![0; order];
+    loop {
+        let n = match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => panic!("Should have read the file: {}", e),
+        };
+
+        for &curr in &buf[..n] {
+            update_cond_occurences(&mut cond_occ, &mut context, curr);
         }
     }
 
     return cond_occ;
 }
-*/
-
-// compute total occurences from the conditional occurences table
-fn count_total_occurences(file: &mut File) -> TotalOccurenceTable {
-    let mut total_occ = [0; ALPHABET_SIZE];
-    let mut buf: [u8; BUFFER_SIZE] = [0; BUFFER_SIZE];
-
-    loop {
-        let n = file.read(&mut buf).expect("Should have read the file");
 
-        for c in buf {
-            total_occ[c as usize] += 1;
-        }
+fn count_cond_occurences_from_bytes(bytes: &[u8], order: usize) -> ConditionalOccurenceTable {
+    let mut cond_occ: ConditionalOccurenceTable = HashMap::new();
 
-        if n < BUFFER_SIZE {
-            break;
-        }
+    let mut context: Context = vec![0; order];
+    for &curr in bytes {
+        update_cond_occurences(&mut cond_occ, &mut context, curr);
     }
 
-    return total_occ;
+    return cond_occ;
 }
 
-fn count_total_occurences_from_bytes(bytes: &[u8]) -> TotalOccurenceTable {
-    let mut total_occ = [0; ALPHABET_SIZE];
+fn update_cond_occurences(cond_occ: &mut ConditionalOccurenceTable, context: &mut Context, curr: u8) {
+    *cond_occ
+        .entry(context.clone())
+        .or_default()
+        .entry(curr)
+        .or_insert(0) += 1;
 
-    for c in bytes {
-        total_occ[*c as usize] += 1;
-    }
+    // shift the context by the current byte
 
-    return total_occ;
+    if !context.is_empty() {
+        context.remove(0);
+        context.push(curr);
+    }
 }
 
-/*
-fn count_total_prev_occurences(cond_occ: ConditionalOccurenceTable) -> TotalOccurenceTable {
-    let mut total_prev_occ = [0; ALPHABET_SIZE];
+pub fn calculate_entropy<R: Read>(reader: &mut R) -> f64 {
+    let mut accumulator = EntropyAccumulator::new();
+    accumulator.update_from_reader(reader).expect("Should have read the file");
+    return accumulator.entropy();
+}
 
-    for prev in 0..ALPHABET_SIZE {
-        for letter in 0..ALPHABET_SIZE {
-            total_prev_occ[prev] += cond_occ[letter][prev];
-        }
-    }
-    
-    return total_prev_occ;
+pub fn calculate_entropy_from_bytes(bytes: &[u8]) -> f64 {
+    let mut accumulator = EntropyAccumulator::new();
+    accumulator.update(bytes);
+    return accumulator.entropy();
 }
-*/
 
-pub fn calculate_entropy(file: &mut File) -> f64 {
-    let mut entropy = 0.0;
+fn cond_entropy_from_occurences(cond_occ: &ConditionalOccurenceTable) -> f64 {
+    let mut cond_entropy = 0.0;
 
-    let total_occ = count_total_occurences(file);
-    let total_length: usize = total_occ.iter().map(|&v| v).sum();
+    let total_length: usize = cond_occ
+        .values()
+        .flat_map(|occ| occ.values())
+        .sum();
 
-    for letter in 0..ALPHABET_SIZE {
-        let occurence_pbb: f64 = total_occ[letter] as f64 / total_length as f64;
-        if occurence_pbb > 0.0 {
-            entropy += -occurence_pbb * f64::log2(occurence_pbb);
-        }
-    }
+    for occ in cond_occ.values() {
+        let context_length: usize = occ.values().sum();
+        let context_pbb: f64 = context_length as f64 / total_length as f64;
 
-    return entropy;
-}
+        // add the entropy of the bytes following the context, weighted by its probability
 
-pub fn calculate_entropy_from_bytes(bytes: &[u8]) -> f64 {
-    let mut entropy = 0.0;
-
-    let total_occ = count_total_occurences_from_bytes(bytes);
-    let total_length: usize = total_occ.iter().map(|&v| v).sum();
-    
-    for letter in 0..ALPHABET_SIZE {
-        let occurence_pbb: f64 = total_occ[letter] as f64 / total_length as f64;
-        if occurence_pbb > 0.0 {
-            entropy += -occurence_pbb * f64::log2(occurence_pbb);
+        for &letter_occ in occ.values() {
+            let occurence_pbb: f64 = letter_occ as f64 / context_length as f64;
+            cond_entropy += -context_pbb * occurence_pbb * f64::log2(occurence_pbb);
         }
     }
 
-    return entropy;
+    return cond_entropy;
 }
 
-/*
-pub fn calculate_conditional_entropy(file: &mut File) -> f64 {
-    let mut cond_entropy = 0.0;
+// compute the conditional entropy H(X | {order} previous bytes); order 0 yields the plain entropy
 
-    let cond_occ = count_cond_occurences(file);
-    let total_occ = (0..ALPHABET_SIZE)
-        .map(|prev| cond_occ
-            .iter()
-            .map(|occ| occ[prev])
-            .sum()
-        )
-    let total_length = total_occ.iter().sum();
-
-    for prev in 0..ALPHABET_SIZE {
-        let prev_total_occurence_pbb: f64 = total_occ[prev] as f64 / total_length as f64
-    }
+pub fn calculate_conditional_entropy<R: Read>(reader: &mut R, order: usize) -> f64 {
+    let cond_occ = count_cond_occurences(reader, order);
+    return cond_entropy_from_occurences(&cond_occ);
 }
-*/
 
+pub fn calculate_conditional_entropy_from_bytes(bytes: &[u8], order: usize) -> f64 {
+    let cond_occ = count_cond_occurences_from_bytes(bytes, order);
+    return cond_entropy_from_occurences(&cond_occ);
+}
@@ -1,11 +1,103 @@
 use std::env;
 use std::fs::File;
 use entropy::*;
+use entropy::measures::*;
+use entropy::profile::*;
+use entropy::symbols::*;
+
+fn print_profile(args: &[String]) {
+    if args.len() < 4 {
+        println!("Usage: path/to/programme profile <file> <window-size> [step] [csv|json]");
+        std::process::exit(1);
+    }
+
+    let mut file = File::open(&args[2]).expect("Can't read the file");
+    let window_size: usize = match args[3].parse() {
+        Ok(window_size) if window_size > 0 => window_size,
+        _ => {println!("Window size should be a positive integer"); std::process::exit(1);},
+    };
+    let step: usize = match args.get(4).map(|step| step.parse()) {
+        Some(Ok(step)) if step > 0 => step,
+        None => window_size,
+        _ => {println!("Step should be a positive integer"); std::process::exit(1);},
+    };
+
+    let profile = calculate_entropy_profile(&mut file, window_size, step).expect("Can't read the file");
+
+    match args.get(5).map(|format| format.as_str()) {
+        None | Some("csv") => print!("{}", profile_to_csv(&profile)),
+        Some("json") => print!("{}", profile_to_json(&profile)),
+        _ => {println!("Invalid output format"); std::process::exit(1);},
+    }
+}
+
+fn print_symbol_entropy(args: &[String]) {
+    if args.len() < 4 {
+        println!("Usage: path/to/programme symbols <file> <bits|u16le|u16be|utf8>");
+        std::process::exit(1);
+    }
+
+    let bytes = std::fs::read(&args[2]).expect("Can't read the file");
+
+    let entropy = match args[3].as_str() {
+        "bits" => calculate_bit_entropy(&bytes),
+        "u16le" => calculate_word_entropy(&bytes, &Endianness::LITTLE),
+        "u16be" => calculate_word_entropy(&bytes, &Endianness::BIG),
+        "utf8" => calculate_utf8_entropy(&bytes).expect("The file should be valid UTF-8"),
+        _ => {println!("Invalid symbol type"); std::process::exit(1);},
+    };
+    println!("entropy = {}", entropy);
+}
+
+fn print_comparison(args: &[String]) {
+    if args.len() < 4 {
+        println!("Usage: path/to/programme compare <file-p> <file-q>");
+        std::process::exit(1);
+    }
+
+    let p = std::fs::read(&args[2]).expect("Can't read the file");
+    let q = std::fs::read(&args[3]).expect("Can't read the file");
+
+    println!("entropy H(P) = {}", calculate_entropy_from_bytes(&p));
+    println!("entropy H(Q) = {}", calculate_entropy_from_bytes(&q));
+    println!("cross-entropy H(P, Q) = {}", calculate_cross_entropy_from_bytes(&p, &q));
+    println!("KL divergence D(P || Q) = {}", calculate_kl_divergence_from_bytes(&p, &q));
+    println!("KL divergence D(Q || P) = {}", calculate_kl_divergence_from_bytes(&q, &p));
+    println!("joint entropy H(X, Y) = {}", calculate_joint_entropy_from_bytes(&p, &q));
+    println!("mutual information I(X; Y) = {}", calculate_mutual_information_from_bytes(&p, &q));
+}
 
 fn main() {
     let args: Vec<String>  = env::args().collect();
+
+    if args.len() > 1 && args[1] == "profile" {
+        print_profile(&args);
+        return;
+    }
+
+    if args.len() > 1 && args[1] == "compare" {
+        print_comparison(&args);
+        return;
+    }
+
+    if args.len() > 1 && args[1] == "symbols" {
+        print_symbol_entropy(&args);
+        return;
+    }
+
     let mut file = File::open(&args[1]).expect("Can't read the file");
 
     let entropy = calculate_entropy(&mut file);
     println!("entropy = {}", entropy);
+
+    // optionally compute the conditional entropies up to the specified order
+
+    if args.len() > 2 {
+        let max_order: usize = args[2].parse().expect("Order should be a non-negative integer");
+        for order in 1..=max_order {
+            let mut file = File::open(&args[1]).expect("Can't read the file");
+            let cond_entropy = calculate_conditional_entropy(&mut file, order);
+            println!("conditional entropy (order {}) = {}", order, cond_entropy);
+        }
+    }
 }
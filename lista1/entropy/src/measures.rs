@@ -0,0 +1,73 @@
+use crate::ALPHABET_SIZE;
+use crate::accumulator::EntropyAccumulator;
+use crate::symbols::calculate_symbol_entropy;
+
+// compute the cross-entropy H(P, Q) of the data distribution P against the model distribution Q;
+// infinite if the data contains a byte the model assigns zero probability to
+
+pub fn cross_entropy(data: &EntropyAccumulator, model: &EntropyAccumulator) -> f64 {
+    let mut cross_entropy = 0.0;
+
+    for letter in 0..ALPHABET_SIZE {
+        let data_pbb: f64 = data.occurences()[letter] as f64 / data.total_length() as f64;
+        let model_pbb: f64 = model.occurences()[letter] as f64 / model.total_length() as f64;
+        if data_pbb > 0.0 {
+            cross_entropy += -data_pbb * f64::log2(model_pbb);
+        }
+    }
+
+    return cross_entropy;
+}
+
+// compute the Kullback-Leibler divergence D(P || Q) = H(P, Q) - H(P)
+
+pub fn kl_divergence(p: &EntropyAccumulator, q: &EntropyAccumulator) -> f64 {
+    let mut divergence = 0.0;
+
+    for letter in 0..ALPHABET_SIZE {
+        let p_pbb: f64 = p.occurences()[letter] as f64 / p.total_length() as f64;
+        let q_pbb: f64 = q.occurences()[letter] as f64 / q.total_length() as f64;
+        if p_pbb > 0.0 {
+            divergence += p_pbb * f64::log2(p_pbb / q_pbb);
+        }
+    }
+
+    return divergence;
+}
+
+pub fn calculate_cross_entropy_from_bytes(data: &[u8], model: &[u8]) -> f64 {
+    let mut data_statistics = EntropyAccumulator::new();
+    let mut model_statistics = EntropyAccumulator::new();
+    data_statistics.update(data);
+    model_statistics.update(model);
+    return cross_entropy(&data_statistics, &model_statistics);
+}
+
+pub fn calculate_kl_divergence_from_bytes(p: &[u8], q: &[u8]) -> f64 {
+    let mut p_statistics = EntropyAccumulator::new();
+    let mut q_statistics = EntropyAccumulator::new();
+    p_statistics.update(p);
+    q_statistics.update(q);
+    return kl_divergence(&p_statistics, &q_statistics);
+}
+
+// the streams are assumed to be aligned; the longer one is truncated to the length of the shorter
+
+pub fn calculate_joint_entropy_from_bytes(x: &[u8], y: &[u8]) -> f64 {
+    return calculate_symbol_entropy(x.iter().zip(y.iter()));
+}
+
+// compute the mutual information I(X; Y) = H(X) + H(Y) - H(X, Y) of two aligned streams
+
+pub fn calculate_mutual_information_from_bytes(x: &[u8], y: &[u8]) -> f64 {
+    let length = x.len().min(y.len());
+    let (x, y) = (&x[..length], &y[..length]);
+
+    let x_entropy = calculate_symbol_entropy(x);
+    let y_entropy = calculate_symbol_entropy(y);
+    let joint_entropy = calculate_joint_entropy_from_bytes(x, y);
+
+    // clamp rounding errors, as the mutual information is never negative
+
+    return (x_entropy + y_entropy - joint_entropy).max(0.0);
+}
@@ -0,0 +1,102 @@
+use std::collections::VecDeque;
+use std::io;
+use std::io::Read;
+use crate::BUFFER_SIZE;
+use crate::accumulator::EntropyAccumulator;
+
+// the entropy of a single window of the data
+
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(Debug)]
+pub struct WindowEntropy {
+    pub offset: usize,
+    pub length: usize,
+    pub entropy: f64,
+}
+
+// compute the entropy of windows of {window_size} bytes, starting every {step} bytes;
+// step == window_size yields fixed-size blocks, step < window_size a sliding window;
+// the trailing bytes not covered by any full window are reported as a shorter window;
+// a zero window size or step is an InvalidInput error
+
+pub fn calculate_entropy_profile<R: Read>(reader: &mut R, window_size: usize, step: usize) -> io::Result<Vec<WindowEntropy>> {
+    if window_size == 0 || step == 0 {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "window size and step should be positive"));
+    }
+
+    let mut profile = Vec::new();
+    let mut buf: [u8; BUFFER_SIZE] = [0; BUFFER_SIZE];
+
+    let mut window: VecDeque<u8> = VecDeque::with_capacity(window_size);
+    let mut statistics = EntropyAccumulator::new();
+    let mut offset = 0;
+    let mut to_skip = 0;
+    let mut unreported = 0;
+
+    loop {
+        let n = match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+
+        for &byte in &buf[..n] {
+
+            // skip the bytes between the windows if the step exceeds the window size
+
+            if to_skip > 0 {
+                to_skip -= 1;
+                continue;
+            }
+
+            window.push_back(byte);
+            statistics.update(&[byte]);
+            unreported += 1;
+
+            if window.len() == window_size {
+                profile.push(WindowEntropy{offset, length: window_size, entropy: statistics.entropy()});
+                unreported = 0;
+
+                // slide the window
+
+                for _ in 0..step.min(window_size) {
+                    let old = window.pop_front().unwrap();
+                    statistics.remove(&[old]);
+                }
+                offset += step;
+                to_skip = step.saturating_sub(window_size);
+            }
+        }
+    }
+
+    if unreported > 0 {
+        profile.push(WindowEntropy{offset, length: window.len(), entropy: statistics.entropy()});
+    }
+
+    return Ok(profile);
+}
+
+// reading the bytes can't fail, so an error means a zero window size or step
+
+pub fn calculate_entropy_profile_from_bytes(bytes: &[u8], window_size: usize, step: usize) -> io::Result<Vec<WindowEntropy>> {
+    let mut reader = bytes;
+    return calculate_entropy_profile(&mut reader, window_size, step);
+}
+
+pub fn profile_to_csv(profile: &[WindowEntropy]) -> String {
+    let mut csv = String::from("offset,length,entropy\n");
+    for window in profile {
+        csv += &format!("{},{},{}\n", window.offset, window.length, window.entropy);
+    }
+    return csv;
+}
+
+pub fn profile_to_json(profile: &[WindowEntropy]) -> String {
+    let windows: Vec<String> = profile
+        .iter()
+        .map(|window| format!("{{\"offset\":{},\"length\":{},\"entropy\":{}}}", window.offset, window.length, window.entropy))
+        .collect();
+    return format!("[{}]\n", windows.join(","));
+}
@@ -0,0 +1,75 @@
+use std::fmt;
+use std::time::Duration;
+
+/* statistics of a single compression run, reported by the coders of the other crates */
+
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(PartialEq)]
+pub struct CompressionStats {
+    pub input_len: u64,
+
+    // the whole output, headers included
+
+    pub output_len: u64,
+
+    // entropy of the input bytes, in bits per byte
+
+    pub entropy: f64,
+    pub bits_per_symbol: f64,
+
+    // input length over output length
+
+    pub ratio: f64,
+    pub elapsed: Duration,
+}
+
+impl CompressionStats {
+    pub fn new(input_len: u64, output_len: u64, entropy: f64, elapsed: Duration) -> Self {
+        return Self {
+            input_len,
+            output_len,
+            entropy,
+            bits_per_symbol: output_len as f64 * 8.0 / input_len as f64,
+            ratio: input_len as f64 / output_len as f64,
+            elapsed,
+        };
+    }
+
+    pub fn throughput(&self) -> f64 {
+        return self.input_len as f64 / self.elapsed.as_secs_f64();
+    }
+
+    pub fn to_json(&self) -> String {
+        return format!(
+            "{{\"input_len\":{},\"output_len\":{},\"entropy\":{},\"bits_per_symbol\":{},\"ratio\":{},\"elapsed_secs\":{},\"throughput\":{}}}\n",
+            self.input_len,
+            self.output_len,
+            json_number(self.entropy),
+            json_number(self.bits_per_symbol),
+            json_number(self.ratio),
+            json_number(self.elapsed.as_secs_f64()),
+            json_number(self.throughput()),
+        );
+    }
+}
+
+// JSON has no infinities nor NaNs, e.g. for an empty input
+
+fn json_number(value: f64) -> String {
+    return match value.is_finite() {
+        true => value.to_string(),
+        false => String::from("null"),
+    };
+}
+
+impl fmt::Display for CompressionStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "input length = {}", self.input_len)?;
+        writeln!(f, "output length = {}", self.output_len)?;
+        writeln!(f, "entropy = {}", self.entropy)?;
+        writeln!(f, "bits per symbol = {}", self.bits_per_symbol)?;
+        writeln!(f, "compression ratio = {}", self.ratio)?;
+        return writeln!(f, "elapsed = {:?}", self.elapsed);
+    }
+}
@@ -0,0 +1,66 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::str::Utf8Error;
+
+const BYTE_SIZE: u8 = 8;
+
+pub enum Endianness {
+    LITTLE,
+    BIG,
+}
+
+// split the bytes into bits, most significant first
+
+pub fn bytes_to_bits(bytes: &[u8]) -> impl Iterator<Item = u8> + '_ {
+    return bytes
+        .iter()
+        .flat_map(|byte| (0..BYTE_SIZE).rev().map(move |pos| (byte >> pos) & 1));
+}
+
+// join consecutive pairs of bytes into 16-bit words; a trailing odd byte is ignored
+
+pub fn bytes_to_words<'a>(bytes: &'a [u8], endianness: &Endianness) -> impl Iterator<Item = u16> + 'a {
+    let from_bytes = match endianness {
+        Endianness::LITTLE => u16::from_le_bytes,
+        Endianness::BIG => u16::from_be_bytes,
+    };
+    return bytes
+        .chunks_exact(2)
+        .map(move |pair| from_bytes([pair[0], pair[1]]));
+}
+
+// compute the entropy of any stream of symbols, e.g. the indices produced by a dictionary coder
+
+pub fn calculate_symbol_entropy<T, I>(symbols: I) -> f64
+where
+    T: Eq + Hash,
+    I: IntoIterator<Item = T>,
+{
+    let mut occurences: HashMap<T, usize> = HashMap::new();
+    let mut total_length = 0;
+    for symbol in symbols {
+        *occurences.entry(symbol).or_insert(0) += 1;
+        total_length += 1;
+    }
+
+    let mut entropy = 0.0;
+    for &symbol_occ in occurences.values() {
+        let occurence_pbb: f64 = symbol_occ as f64 / total_length as f64;
+        entropy += -occurence_pbb * f64::log2(occurence_pbb);
+    }
+
+    return entropy;
+}
+
+pub fn calculate_bit_entropy(bytes: &[u8]) -> f64 {
+    return calculate_symbol_entropy(bytes_to_bits(bytes));
+}
+
+pub fn calculate_word_entropy(bytes: &[u8], endianness: &Endianness) -> f64 {
+    return calculate_symbol_entropy(bytes_to_words(bytes, endianness));
+}
+
+pub fn calculate_utf8_entropy(bytes: &[u8]) -> Result<f64, Utf8Error> {
+    let text = std::str::from_utf8(bytes)?;
+    return Ok(calculate_symbol_entropy(text.chars()));
+}
@@ -0,0 +1,156 @@
+use std::cmp::min;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom, Write};
+use entropy::*;
+use entropy::accumulator::EntropyAccumulator;
+use entropy::measures::*;
+use entropy::profile::*;
+use entropy::symbols::*;
+use proptest::prelude::*;
+
+const EPSILON: f64 = 1e-9;
+
+// a reader returning at most {chunk} bytes per call, to exercise short reads
+
+struct ShortReader<'a> {
+    data: &'a [u8],
+    chunk: usize,
+}
+
+impl Read for ShortReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = min(min(self.chunk, buf.len()), self.data.len());
+        buf[..n].copy_from_slice(&self.data[..n]);
+        self.data = &self.data[n..];
+        return Ok(n);
+    }
+}
+
+fn write_temp_file(bytes: &[u8]) -> File {
+    let mut file = tempfile::tempfile().unwrap();
+    file.write_all(bytes).unwrap();
+    file.seek(SeekFrom::Start(0)).unwrap();
+    return file;
+}
+
+#[test]
+fn entropy_of_uneven_file() {
+    let bytes: Vec<u8> = (0..33).collect();
+    let mut file = write_temp_file(&bytes);
+    assert!((calculate_entropy(&mut file) - f64::log2(33.0)).abs() < EPSILON);
+}
+
+proptest! {
+    #[test]
+    fn file_entropy_matches_bytes(bytes in proptest::collection::vec(any::<u8>(), 0..2000)) {
+        let mut file = write_temp_file(&bytes);
+        prop_assert!((calculate_entropy(&mut file) - calculate_entropy_from_bytes(&bytes)).abs() < EPSILON);
+    }
+
+    #[test]
+    fn short_reads_entropy_matches_bytes(bytes in proptest::collection::vec(any::<u8>(), 0..2000), chunk in 1usize..64) {
+        let mut reader = ShortReader{data: &bytes, chunk};
+        prop_assert!((calculate_entropy(&mut reader) - calculate_entropy_from_bytes(&bytes)).abs() < EPSILON);
+    }
+
+    #[test]
+    fn entropy_is_bounded(bytes in proptest::collection::vec(any::<u8>(), 0..2000)) {
+        let entropy = calculate_entropy_from_bytes(&bytes);
+        prop_assert!((0.0..=8.0 + EPSILON).contains(&entropy));
+        prop_assert!(entropy <= f64::log2(bytes.len().max(1) as f64) + EPSILON);
+    }
+
+    #[test]
+    fn merged_accumulators_match_whole(bytes in proptest::collection::vec(any::<u8>(), 0..2000), split in any::<prop::sample::Index>()) {
+        let split = split.index(bytes.len() + 1);
+        let mut left = EntropyAccumulator::new();
+        let mut right = EntropyAccumulator::new();
+        left.update(&bytes[..split]);
+        right.update(&bytes[split..]);
+        left.merge(&right);
+
+        let mut whole = EntropyAccumulator::new();
+        whole.update(&bytes);
+
+        prop_assert_eq!(left.occurences(), whole.occurences());
+        prop_assert_eq!(left.total_length(), bytes.len());
+        prop_assert!((left.entropy() - whole.entropy()).abs() < EPSILON);
+    }
+
+    #[test]
+    fn file_conditional_entropy_matches_bytes(bytes in proptest::collection::vec(any::<u8>(), 0..2000), order in 0usize..4) {
+        let mut file = write_temp_file(&bytes);
+        let file_entropy = calculate_conditional_entropy(&mut file, order);
+        prop_assert!((file_entropy - calculate_conditional_entropy_from_bytes(&bytes, order)).abs() < EPSILON);
+    }
+
+    #[test]
+    fn conditioning_does_not_increase_entropy(bytes in proptest::collection::vec(0u8..8, 0..2000)) {
+        let mut prev_entropy = calculate_entropy_from_bytes(&bytes);
+        for order in 0..4 {
+            let cond_entropy = calculate_conditional_entropy_from_bytes(&bytes, order);
+            prop_assert!(cond_entropy <= prev_entropy + EPSILON);
+            prev_entropy = cond_entropy;
+        }
+    }
+
+    #[test]
+    fn block_profile_matches_chunks(bytes in proptest::collection::vec(any::<u8>(), 0..2000), block in 1usize..100) {
+        let profile = calculate_entropy_profile_from_bytes(&bytes, block, block).unwrap();
+        prop_assert_eq!(profile.len(), bytes.chunks(block).count());
+        for (window, chunk) in profile.iter().zip(bytes.chunks(block)) {
+            prop_assert_eq!(window.length, chunk.len());
+            prop_assert!((window.entropy - calculate_entropy_from_bytes(chunk)).abs() < EPSILON);
+        }
+    }
+
+    #[test]
+    fn sliding_profile_matches_windows(bytes in proptest::collection::vec(any::<u8>(), 1..1000), window_size in 1usize..64, step in 1usize..64) {
+        let profile = calculate_entropy_profile_from_bytes(&bytes, window_size, step).unwrap();
+        for window in profile {
+            let chunk = &bytes[window.offset..(window.offset + window.length)];
+            prop_assert!((window.entropy - calculate_entropy_from_bytes(chunk)).abs() < EPSILON);
+        }
+    }
+
+    #[test]
+    fn symbol_entropy_of_bytes_matches_byte_entropy(bytes in proptest::collection::vec(any::<u8>(), 0..2000)) {
+        prop_assert!((calculate_symbol_entropy(&bytes) - calculate_entropy_from_bytes(&bytes)).abs() < EPSILON);
+        prop_assert!(calculate_bit_entropy(&bytes) <= 1.0 + EPSILON);
+    }
+
+    #[test]
+    fn utf8_entropy_of_ascii_matches_byte_entropy(text in "[ -~]{0,500}") {
+        let utf8_entropy = calculate_utf8_entropy(text.as_bytes()).unwrap();
+        prop_assert!((utf8_entropy - calculate_entropy_from_bytes(text.as_bytes())).abs() < EPSILON);
+    }
+
+    #[test]
+    fn cross_entropy_is_entropy_plus_divergence(p in proptest::collection::vec(any::<u8>(), 1..1000), q in proptest::collection::vec(any::<u8>(), 1..1000)) {
+        let mut q = q;
+        q.extend(&p);
+        let divergence = calculate_kl_divergence_from_bytes(&p, &q);
+        prop_assert!(divergence >= -EPSILON);
+        prop_assert!(calculate_kl_divergence_from_bytes(&p, &p).abs() < EPSILON);
+        let cross_entropy = calculate_cross_entropy_from_bytes(&p, &q);
+        prop_assert!((cross_entropy - calculate_entropy_from_bytes(&p) - divergence).abs() < 1e-6);
+    }
+
+    #[test]
+    fn mutual_information_properties(x in proptest::collection::vec(any::<u8>(), 0..1000), y in proptest::collection::vec(any::<u8>(), 0..1000)) {
+        prop_assert!((calculate_mutual_information_from_bytes(&x, &x) - calculate_entropy_from_bytes(&x)).abs() < 1e-6);
+        let xy = calculate_mutual_information_from_bytes(&x, &y);
+        let yx = calculate_mutual_information_from_bytes(&y, &x);
+        prop_assert!((xy - yx).abs() < 1e-6);
+        prop_assert!(calculate_joint_entropy_from_bytes(&x, &y) <= 16.0 + EPSILON);
+    }
+}
+
+#[test]
+fn profile_rejects_empty_windows() {
+    for (window_size, step) in [(0, 1), (1, 0), (0, 0)] {
+        let error = calculate_entropy_profile_from_bytes(b"abc", window_size, step).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+        assert!(calculate_entropy_profile(&mut &b"abc"[..], window_size, step).is_err());
+    }
+}
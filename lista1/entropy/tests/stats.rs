@@ -0,0 +1,13 @@
+use std::time::Duration;
+use entropy::stats::CompressionStats;
+
+#[test]
+fn compression_stats_json() {
+    let stats = CompressionStats::new(100, 25, 2.0, Duration::from_millis(500));
+    assert_eq!(stats.bits_per_symbol, 2.0);
+    assert_eq!(stats.ratio, 4.0);
+    assert_eq!(stats.to_json(), "{\"input_len\":100,\"output_len\":25,\"entropy\":2,\"bits_per_symbol\":2,\"ratio\":4,\"elapsed_secs\":0.5,\"throughput\":200}\n");
+
+    let empty = CompressionStats::new(0, 0, 0.0, Duration::ZERO);
+    assert!(empty.to_json().contains("\"ratio\":null"));
+}
@@ -0,0 +1,199 @@
+use std::io::Read;
+use std::io::Write;
+use crate::coder::{Decoder, Encoder};
+use crate::error::ArithmeticError;
+use crate::range_coder::{RangeDecoder, RangeEncoder};
+
+/* adaptive binary arithmetic coding of bit streams, e.g. flags or residual bit planes;
+ * every context keeps the probability of a zero, moved towards each coded bit by a fixed fraction */
+
+const PROBABILITY_BITS: u32 = 12;
+const PROBABILITY_TOTAL: u64 = 1 << PROBABILITY_BITS;
+const DEFAULT_SHIFT: u32 = 5;
+
+// the shift never lets the probability reach 0 or 1, so both bits always stay codable
+
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(Debug)]
+pub struct BitModel {
+    zero_probability: u64,
+    shift: u32,
+}
+
+impl BitModel {
+    pub fn new() -> Self {
+        return Self::with_shift(DEFAULT_SHIFT);
+    }
+
+    // smaller shifts adapt faster, larger ones estimate stationary sources more accurately
+
+    pub fn with_shift(shift: u32) -> Self {
+        return Self {
+            zero_probability: PROBABILITY_TOTAL / 2,
+            shift: shift.clamp(1, PROBABILITY_BITS - 2),
+        };
+    }
+
+    pub fn zero_probability(&self) -> f64 {
+        return self.zero_probability as f64 / PROBABILITY_TOTAL as f64;
+    }
+
+    fn interval(&self, bit: bool) -> (u64, u64) {
+        return match bit {
+            false => (0, self.zero_probability),
+            true => (self.zero_probability, PROBABILITY_TOTAL),
+        };
+    }
+
+    fn update(&mut self, bit: bool) {
+        match bit {
+            false => self.zero_probability += (PROBABILITY_TOTAL - self.zero_probability) >> self.shift,
+            true => self.zero_probability -= self.zero_probability >> self.shift,
+        }
+    }
+}
+
+impl Default for BitModel {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+pub fn encode_bit<E: Encoder>(encoder: &mut E, model: &mut BitModel, bit: bool) -> Result<(), ArithmeticError> {
+    let (cum_left, cum_right) = model.interval(bit);
+    encoder.encode(cum_left, cum_right, PROBABILITY_TOTAL)?;
+    model.update(bit);
+    return Ok(());
+}
+
+pub fn decode_bit<D: Decoder>(decoder: &mut D, model: &mut BitModel) -> Result<bool, ArithmeticError> {
+    let bit = decoder.target(PROBABILITY_TOTAL)? >= model.zero_probability;
+    let (cum_left, cum_right) = model.interval(bit);
+    decoder.consume(cum_left, cum_right, PROBABILITY_TOTAL)?;
+    model.update(bit);
+    return Ok(bit);
+}
+
+/* binary coders over the range coder; the caller chooses a model (context) for every bit
+ * and has to make the same choices, for the same number of bits, when decoding */
+
+pub struct BinaryEncoder<W: Write> {
+    coder: RangeEncoder<W>,
+}
+
+impl<W: Write> BinaryEncoder<W> {
+    pub fn new(out: W) -> Self {
+        return Self {
+            coder: RangeEncoder::new(out),
+        };
+    }
+
+    pub fn encode(&mut self, bit: bool, model: &mut BitModel) -> Result<(), ArithmeticError> {
+        return encode_bit(&mut self.coder, model, bit);
+    }
+
+    // returns the number of bits written
+
+    pub fn finish(self) -> Result<u64, ArithmeticError> {
+        return self.coder.finish();
+    }
+}
+
+pub struct BinaryDecoder<R: Read> {
+    coder: RangeDecoder<R>,
+}
+
+impl<R: Read> BinaryDecoder<R> {
+    pub fn new(input: R) -> Result<Self, ArithmeticError> {
+        return Ok(Self {
+            coder: RangeDecoder::new(input)?,
+        });
+    }
+
+    pub fn decode(&mut self, model: &mut BitModel) -> Result<bool, ArithmeticError> {
+        return decode_bit(&mut self.coder, model);
+    }
+}
+
+// bits of a stream are modelled in the context of the two preceding ones
+
+const STREAM_CONTEXT_BITS: usize = 2;
+
+pub fn encode_bits<I: IntoIterator<Item = bool>>(bits: I) -> Result<Vec<u8>, ArithmeticError> {
+    let mut coded = Vec::new();
+    let mut encoder = BinaryEncoder::new(&mut coded);
+    let mut models = [BitModel::new(); 1 << STREAM_CONTEXT_BITS];
+    let mut context = 0;
+
+    for bit in bits {
+        encoder.encode(bit, &mut models[context])?;
+        context = ((context << 1) | bit as usize) & ((1 << STREAM_CONTEXT_BITS) - 1);
+    }
+
+    encoder.finish()?;
+    return Ok(coded);
+}
+
+pub fn decode_bits(coded: &[u8], bits_len: usize) -> Result<Vec<bool>, ArithmeticError> {
+    let mut decoder = BinaryDecoder::new(coded)?;
+    let mut models = [BitModel::new(); 1 << STREAM_CONTEXT_BITS];
+    let mut context = 0;
+
+    let mut bits = Vec::with_capacity(bits_len);
+    for _ in 0..bits_len {
+        let bit = decoder.decode(&mut models[context])?;
+        context = ((context << 1) | bit as usize) & ((1 << STREAM_CONTEXT_BITS) - 1);
+        bits.push(bit);
+    }
+
+    return Ok(bits);
+}
+
+// code the bytes plane by plane from the most significant one; a bit is modelled in the context
+// of its plane, the more significant bits of the same byte and the bit at the same position of the previous byte
+
+fn plane_context(plane: usize, byte: u8, previous: u8) -> usize {
+    let higher = (byte as usize) >> (8 - plane);
+    let above = ((previous >> (7 - plane)) & 1) as usize;
+    return ((1 << plane) + higher) * 2 + above;
+}
+
+pub fn encode_bit_planes(bytes: &[u8]) -> Result<Vec<u8>, ArithmeticError> {
+    let mut coded = Vec::new();
+    let mut encoder = BinaryEncoder::new(&mut coded);
+    let mut models = vec![BitModel::new(); 512];
+
+    for plane in 0..8 {
+        let mut previous = 0;
+        for &byte in bytes {
+            let context = plane_context(plane, byte, previous);
+            encoder.encode((byte >> (7 - plane)) & 1 == 1, &mut models[context])?;
+            previous = byte;
+        }
+    }
+
+    encoder.finish()?;
+    return Ok(coded);
+}
+
+pub fn decode_bit_planes(coded: &[u8], bytes_len: usize) -> Result<Vec<u8>, ArithmeticError> {
+    let mut decoder = BinaryDecoder::new(coded)?;
+    let mut models = vec![BitModel::new(); 512];
+    let mut bytes = vec![0u8; bytes_len];
+
+    for plane in 0..8 {
+        let mut previous = 0;
+        for byte in bytes.iter_mut() {
+
+            // the less significant bits of the bytes are still zeros, as in the encoder's context
+
+            let context = plane_context(plane, *byte, previous);
+            let bit = decoder.decode(&mut models[context])?;
+            *byte |= (bit as u8) << (7 - plane);
+            previous = *byte;
+        }
+    }
+
+    return Ok(bytes);
+}
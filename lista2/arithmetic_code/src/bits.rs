@@ -0,0 +1,121 @@
+use std::io;
+use std::io::Read;
+use std::io::Write;
+
+const BUFFER_SIZE: usize = 4096;
+const WORD_BITS: u32 = 64;
+
+// writes bits most significant first, packing them into a 64-bit word before buffering
+
+pub struct BitWriter<W: Write> {
+    out: W,
+    word: u64,
+    word_len: u32,
+    buffer: Vec<u8>,
+    bits_written: u64,
+}
+
+impl<W: Write> BitWriter<W> {
+    pub fn new(out: W) -> Self {
+        Self {
+            out,
+            word: 0,
+            word_len: 0,
+            buffer: Vec::with_capacity(BUFFER_SIZE),
+            bits_written: 0,
+        }
+    }
+
+    pub fn write_bit(&mut self, bit: bool) -> io::Result<()> {
+        self.word = (self.word << 1) | bit as u64;
+        self.word_len += 1;
+        self.bits_written += 1;
+
+        if self.word_len == WORD_BITS {
+            self.buffer.extend(self.word.to_be_bytes());
+            self.word = 0;
+            self.word_len = 0;
+
+            if self.buffer.len() >= BUFFER_SIZE {
+                self.out.write_all(&self.buffer)?;
+                self.buffer.clear();
+            }
+        }
+
+        return Ok(());
+    }
+
+    // write the same bit {count} times
+
+    pub fn write_bits(&mut self, bit: bool, count: u64) -> io::Result<()> {
+        for _ in 0..count {
+            self.write_bit(bit)?;
+        }
+        return Ok(());
+    }
+
+    pub fn bits_written(&self) -> u64 {
+        return self.bits_written;
+    }
+
+    // pad the last byte with zeros and flush everything to the output
+
+    pub fn finish(mut self) -> io::Result<W> {
+        let no_bytes = self.word_len.div_ceil(8) as usize;
+        if no_bytes > 0 {
+            let padded = self.word << (WORD_BITS - self.word_len);
+            self.buffer.extend(&padded.to_be_bytes()[..no_bytes]);
+        }
+        self.out.write_all(&self.buffer)?;
+        self.out.flush()?;
+        return Ok(self.out);
+    }
+}
+
+// reads bits most significant first; None is returned once the input is exhausted
+
+pub struct BitReader<R: Read> {
+    input: R,
+    buffer: Vec<u8>,
+    buffer_pos: usize,
+    buffer_len: usize,
+    bit_pos: u32,
+}
+
+impl<R: Read> BitReader<R> {
+    pub fn new(input: R) -> Self {
+        Self {
+            input,
+            buffer: vec![0; BUFFER_SIZE],
+            buffer_pos: 0,
+            buffer_len: 0,
+            bit_pos: 0,
+        }
+    }
+
+    pub fn read_bit(&mut self) -> io::Result<Option<bool>> {
+        if self.buffer_pos == self.buffer_len {
+            self.buffer_len = loop {
+                match self.input.read(&mut self.buffer) {
+                    Ok(n) => break n,
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(e) => return Err(e),
+                }
+            };
+            self.buffer_pos = 0;
+
+            if self.buffer_len == 0 {
+                return Ok(None);
+            }
+        }
+
+        let bit = (self.buffer[self.buffer_pos] >> (7 - self.bit_pos)) & 1 == 1;
+        self.bit_pos += 1;
+        if self.bit_pos == 8 {
+            self.bit_pos = 0;
+            self.buffer_pos += 1;
+        }
+
+        return Ok(Some(bit));
+    }
+}
@@ -0,0 +1,51 @@
+use crate::error::ArithmeticError;
+
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(Debug)]
+#[derive(PartialEq)]
+pub enum CoderType {
+    BIT,
+    RANGE,
+}
+
+// both coders keep at least 32 bits of precision, so the total has to fit into them
+
+pub(crate) const MAX_CODER_TOTAL: u64 = 1 << 32;
+
+pub(crate) fn check_total(total: u64) -> Result<(), ArithmeticError> {
+    if total == 0 || total > MAX_CODER_TOTAL {
+        return Err(ArithmeticError::ModelOverflow);
+    }
+    return Ok(());
+}
+
+// the entropy coder narrows its interval to the cumulative interval [cum_left, cum_right) out of {total}
+
+pub trait Encoder {
+    fn encode(&mut self, cum_left: u64, cum_right: u64, total: u64) -> Result<(), ArithmeticError>;
+
+    // flush the rest of the code; returns the number of bits written
+
+    fn finish(self) -> Result<u64, ArithmeticError> where Self: Sized;
+}
+
+// a value below {total}, all of them equally likely; frames the chunks of a streamed text
+
+pub(crate) fn encode_uniform<E: Encoder>(coder: &mut E, value: u64, total: u64) -> Result<(), ArithmeticError> {
+    return coder.encode(value, value + 1, total);
+}
+
+pub(crate) fn decode_uniform<D: Decoder>(coder: &mut D, total: u64) -> Result<u64, ArithmeticError> {
+    let value = coder.target(total)?;
+    coder.consume(value, value + 1, total)?;
+    return Ok(value);
+}
+
+// the decoder first finds the cumulative value the code points at, and once the model
+// recognises the symbol containing it, narrows its interval exactly like the encoder did
+
+pub trait Decoder {
+    fn target(&mut self, total: u64) -> Result<u64, ArithmeticError>;
+    fn consume(&mut self, cum_left: u64, cum_right: u64, total: u64) -> Result<(), ArithmeticError>;
+}
@@ -0,0 +1,118 @@
+use std::io::Read;
+use std::io::Write;
+use crate::coder::CoderType;
+use crate::error::{ArithmeticError, HeaderError};
+use crate::model::{read_varint, write_varint, ModelOptions, ModelType};
+
+/* header of the coded file:
+ *   magic bytes "ACDR", format version,
+ *   model, coder, increment of the model and its limit plus one (0 meaning no limit) needed to decode the text,
+ *   text length plus one as a varint (0 meaning a streamed text), CRC32 of the text (little-endian);
+ * a streamed text is coded in chunks of STREAM_CHUNK bytes, each one preceded by its length and the last one
+ * shorter, and its CRC32 is coded after them, so the header is written before the text is read */
+
+pub const MAGIC: [u8; 4] = *b"ACDR";
+pub const VERSION: u8 = 1;
+pub(crate) const STREAM_CHUNK: u64 = 1 << 16;
+
+#[derive(Debug)]
+#[derive(PartialEq)]
+pub struct Header {
+    pub model: ModelType,
+    pub options: ModelOptions,
+    pub coder: CoderType,
+    pub text_len: u64,
+    pub checksum: u32,
+
+    // the length and checksum follow the code instead of the header
+
+    pub streamed: bool,
+}
+
+fn model_id(model: &ModelType) -> u8 {
+    return match model {
+        ModelType::ORDER0 => 0,
+        ModelType::ORDER1 => 1,
+        ModelType::ORDER2 => 2,
+        ModelType::STATIC => 3,
+    };
+}
+
+fn model_from_id(id: u8) -> Result<ModelType, HeaderError> {
+    return match id {
+        0 => Ok(ModelType::ORDER0),
+        1 => Ok(ModelType::ORDER1),
+        2 => Ok(ModelType::ORDER2),
+        3 => Ok(ModelType::STATIC),
+        _ => Err(HeaderError::UnknownModel(id)),
+    };
+}
+
+fn coder_id(coder: &CoderType) -> u8 {
+    return match coder {
+        CoderType::BIT => 0,
+        CoderType::RANGE => 1,
+    };
+}
+
+fn coder_from_id(id: u8) -> Result<CoderType, HeaderError> {
+    return match id {
+        0 => Ok(CoderType::BIT),
+        1 => Ok(CoderType::RANGE),
+        _ => Err(HeaderError::UnknownCoder(id)),
+    };
+}
+
+impl Header {
+    pub fn write<W: Write>(&self, out: &mut W) -> Result<(), ArithmeticError> {
+        out.write_all(&MAGIC)?;
+        out.write_all(&[VERSION, model_id(&self.model), coder_id(&self.coder)])?;
+        write_varint(out, self.options.increment)?;
+        write_varint(out, self.options.limit.map_or(0, |limit| limit.saturating_add(1)))?;
+        if self.streamed {
+            write_varint(out, 0)?;
+            return Ok(());
+        }
+        write_varint(out, self.text_len.saturating_add(1))?;
+        out.write_all(&self.checksum.to_le_bytes())?;
+        return Ok(());
+    }
+
+    pub fn read<R: Read>(input: &mut R) -> Result<Self, ArithmeticError> {
+        let mut magic = [0u8; 4];
+        input.read_exact(&mut magic)?;
+        if magic != MAGIC {
+            return Err(HeaderError::BadMagic.into());
+        }
+
+        let mut ids = [0u8; 3];
+        input.read_exact(&mut ids)?;
+        if ids[0] != VERSION {
+            return Err(HeaderError::UnsupportedVersion(ids[0]).into());
+        }
+        let model = model_from_id(ids[1])?;
+        let coder = coder_from_id(ids[2])?;
+
+        let increment = read_varint(input)?;
+        let limit = read_varint(input)?;
+        let options = ModelOptions{increment, limit: limit.checked_sub(1)};
+        if options.validate().is_err() {
+            return Err(HeaderError::InvalidOptions.into());
+        }
+
+        let text_len = read_varint(input)?.checked_sub(1);
+        let mut checksum = [0u8; 4];
+        if text_len.is_some() {
+            input.read_exact(&mut checksum)?;
+        }
+
+        return Ok(Self {
+            model,
+            options,
+            coder,
+            text_len: text_len.unwrap_or(0),
+            checksum: u32::from_le_bytes(checksum),
+            streamed: text_len.is_none(),
+        });
+    }
+}
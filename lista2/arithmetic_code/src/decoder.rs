@@ -1,188 +1,193 @@
-use std::error::Error;
-use std::fs;
-use std::fs::File;
+use std::io::BufWriter;
+use std::io::Read;
 use std::io::Write;
+use crc32fast::Hasher;
+use crate::bits::BitReader;
+use crate::coder::{check_total, decode_uniform, CoderType, Decoder};
+use crate::container::{Header, STREAM_CHUNK};
+use crate::error::ArithmeticError;
+use crate::model::*;
+use crate::range_coder::RangeDecoder;
 
-const BUFFER_SIZE: usize = 256;
-const BYTES_RANGE: usize = 256;
 const MAX_HIGH: u128 = 0x00000001000000000000000000000000;
 const MIN_LOW: u128 = 0x00000000000000000000000000000000;
+const INTERVAL_BITS: u32 = 128 - MAX_HIGH.leading_zeros() - 1;
 
 struct Endpoints {
     left: u128,
     right: u128,
 }
 
-fn int_to_bin2(n: u8) -> Vec<char> {
-    let mut bin_rep = Vec::new();
-    let mut bin_value = 0;
-    let mut two = 2u8.pow(7);
-    for _ in 0..8 {
-        if n >= bin_value + two {
-            bin_value += two;
-            bin_rep.push('1');
-        } else {
-            bin_rep.push('0');
-        }
-        two /= 2;
-    }
-    return bin_rep;
+/* bit-level arithmetic decoder keeping the next bits of the code in a tag */
+
+pub struct BitDecoder<R: Read> {
+    interval: Endpoints,
+    tag: u128,
+    code: BitReader<R>,
 }
 
-pub fn decode(file: &String, out: &mut File) -> Result<(), Box<dyn Error>> {
+impl<R: Read> BitDecoder<R> {
 
-    let mut code: Vec<u8> = fs::read(file)?;
+    // read the first bits of the code into the tag; the encoder always writes the whole final tag,
+    // so the decoder never reads past the code unless it is truncated
 
-    // read text length from file
+    pub fn new(input: R) -> Result<Self, ArithmeticError> {
+        let mut code = BitReader::new(input);
+        let mut tag: u128 = 0;
+        for _ in 0..INTERVAL_BITS {
+            tag = (tag << 1) | next_bit(&mut code)? as u128;
+        }
 
-    let mut text_len_bytes = [0u8; 16];
-    let mut i = 0;
-    while code[i] != 0xA {
-        text_len_bytes[i] = code[i];
-        i += 1;
+        return Ok(Self {
+            interval: Endpoints{left: MIN_LOW, right: MAX_HIGH},
+            tag,
+            code,
+        });
     }
-    let _ = code.drain(..=i);
-    let text_len = u128::from_le_bytes(text_len_bytes.try_into().unwrap());
-
-    /* create the necessary structures */
-
-    let mut exit_code = 1;
-
-    let mut occurences: Vec<u128> = Vec::from([1u128; BYTES_RANGE]);
-    let mut cum_occurences: Vec<Endpoints> = occurences.iter().scan(0, |cum_occ, &occ| {
-        let old_cum_occ = *cum_occ;
-        *cum_occ += occ;
-        return Some(Endpoints{left: old_cum_occ, right: *cum_occ});
-    }).collect();
-    let mut total_occurences = BYTES_RANGE as u128;
+}
 
-    let mut decoded: Vec<u8> = Vec::new();
+fn next_bit<R: Read>(code: &mut BitReader<R>) -> Result<bool, ArithmeticError> {
+    return code.read_bit()?.ok_or(ArithmeticError::TruncatedInput);
+}
 
-    let mut interval = Endpoints{left: MIN_LOW, right: MAX_HIGH};
-    let mut tag_interval = Endpoints{left: MIN_LOW, right: MAX_HIGH};
+impl<R: Read> Decoder for BitDecoder<R> {
+    fn target(&mut self, total: u64) -> Result<u64, ArithmeticError> {
+        check_total(total)?;
+        let interval = &self.interval;
+        if self.tag < interval.left || self.tag >= interval.right {
+            return Err(ArithmeticError::CorruptedCode);
+        }
 
-    let interval_len = interval.right - interval.left;
-    let mut possible_bytes: Vec<(u8, Endpoints)> = cum_occurences.iter().enumerate().map(|(byte, cum_occ)| (byte as u8, Endpoints{left: interval.left + interval_len * cum_occ.left / total_occurences, right: interval.left + interval_len * cum_occ.right / total_occurences})).collect();
+        let interval_len = interval.right - interval.left;
+        let target = ((self.tag - interval.left + 1) * total as u128 - 1) / interval_len;
+        return Ok(target as u64);
+    }
 
-    /* read the coded file bit by bit and retrieve the coded bytes */
+    fn consume(&mut self, cum_left: u64, cum_right: u64, total: u64) -> Result<(), ArithmeticError> {
+        check_total(total)?;
+        let interval = &mut self.interval;
 
-    'decodeloop: for byte in code {
+        // restrict the (total) interval to the interval of the identified symbol
 
-        let bits = int_to_bin2(byte);
+        let interval_len = interval.right - interval.left;
+        (interval.left, interval.right) = (interval.left + interval_len * cum_left as u128 / total as u128, interval.left + interval_len * cum_right as u128 / total as u128);
 
-        for bit in bits {
+        // scale the interval, shifting the next bit of the code into the tag
 
-            /* reduce the tag interval */
+        loop {
+            if interval.right <= MAX_HIGH / 2 {
 
-            if bit == '1' {
-                tag_interval.left = tag_interval.left + (tag_interval.right - tag_interval.left) / 2;
-            } else if bit == '0' {
-                tag_interval.right = tag_interval.left + (tag_interval.right - tag_interval.left) / 2;
-            } else {
-                panic!("invalid bit read while decoding: {}", bit);
-            }
+                // scale to the right
 
-            /* retain only the bytes whose intervals overlap with the tag interval */
+                interval.left = 2 * interval.left;
+                interval.right = 2 * interval.right;
+                self.tag = 2 * self.tag;
 
-            possible_bytes.retain(|(_, byte_interval)| byte_interval.left < tag_interval.right && byte_interval.right > tag_interval.left);
+            } else if interval.left >= MAX_HIGH / 2 {
 
-            /* check if a byte can be identified */
+                // scale to the left
 
-            while possible_bytes.len() == 1 {
+                interval.left = 2 * interval.left - MAX_HIGH;
+                interval.right = 2 * interval.right - MAX_HIGH;
+                self.tag = 2 * self.tag - MAX_HIGH;
 
-                // recover the identified byte and append it to the result
+            } else if interval.left >= MAX_HIGH / 4 && interval.right <= MAX_HIGH * 3 / 4 {
 
-                let (byte, _) = possible_bytes[0];
-                decoded.push(byte);
-                
-                // check if the whole text has been recovered
+                // scale both ways
 
-                if decoded.len() as u128 == text_len {
-                    exit_code = 0;
-                    break 'decodeloop;
-                }
+                interval.left = 2 * interval.left - MAX_HIGH / 2;
+                interval.right = 2 * interval.right - MAX_HIGH / 2;
+                self.tag = 2 * self.tag - MAX_HIGH / 2;
 
-                // restrict the (total) interval to the interval of the identified byte
+            } else {
 
-                let interval_len = interval.right - interval.left;
-                (interval.left, interval.right) = (interval.left + interval_len * cum_occurences[byte as usize].left / total_occurences, interval.left + interval_len * cum_occurences[byte as usize].right / total_occurences);
+                // stop scaling; quit the loop
 
-                // scale the interval
+                break;
+            }
 
-                loop {
-                    if interval.right <= MAX_HIGH / 2 {
+            self.tag += next_bit(&mut self.code)? as u128;
+        }
 
-                        // scale to the right
+        return Ok(());
+    }
+}
 
-                        interval.left = 2 * interval.left;
-                        interval.right = 2 * interval.right;
-                        tag_interval.left = 2 * tag_interval.left;
-                        tag_interval.right = 2 * tag_interval.right;
+fn decode_bytes_with<W: Write, M: Model, D: Decoder>(out: &mut W, model: &mut M, coder: &mut D, hasher: &mut Hasher, text_len: u64) -> Result<(), ArithmeticError> {
+    for _ in 0..text_len {
+        let byte = model.decode(coder)?;
+        out.write_all(&[byte])?;
+        hasher.update(&[byte]);
+    }
+    return Ok(());
+}
 
-                    } else if interval.left >= MAX_HIGH / 2 {
-                        
-                        // scale to the left
+// returns the checksum of the decoded text; the length and checksum of a streamed text are read
+// from its code into the header
 
-                        interval.left = 2 * interval.left - MAX_HIGH;
-                        interval.right = 2 * interval.right - MAX_HIGH;
-                        tag_interval.left = 2 * tag_interval.left - MAX_HIGH;
-                        tag_interval.right = 2 * tag_interval.right - MAX_HIGH;
+fn decode_with<W: Write, M: Model, D: Decoder>(out: &mut W, model: &mut M, mut coder: D, header: &mut Header) -> Result<u32, ArithmeticError> {
 
-                    } else if interval.left >= MAX_HIGH / 4 && interval.right <= MAX_HIGH * 3 / 4 {
-                        
-                        // scale both ways
+    let mut out = BufWriter::new(out);
+    let mut hasher = Hasher::new();
 
-                        interval.left = 2 * interval.left - MAX_HIGH / 2;
-                        interval.right = 2 * interval.right - MAX_HIGH / 2;
-                        tag_interval.left = 2 * tag_interval.left - MAX_HIGH / 2;
-                        tag_interval.right = 2 * tag_interval.right - MAX_HIGH / 2;
+    if !header.streamed {
 
-                    } else {
+        /* retrieve the coded bytes one by one */
 
-                        // stop scaling; quit the loop
+        decode_bytes_with(&mut out, model, &mut coder, &mut hasher, header.text_len)?;
+        out.flush()?;
+        return Ok(hasher.finalize());
+    }
 
-                        break;
-                    }
-                }
+    /* retrieve the chunks until the first one shorter than the others, then the checksum */
 
-                // check for bytes' probability recalculation
+    loop {
+        let chunk_len = decode_uniform(&mut coder, STREAM_CHUNK + 1)?;
+        decode_bytes_with(&mut out, model, &mut coder, &mut hasher, chunk_len)?;
+        header.text_len += chunk_len;
 
-                if decoded.len() % BUFFER_SIZE == 0 {
-                    for i in (decoded.len() - BUFFER_SIZE)..decoded.len() {
-                        let byte = &decoded[i];
-                        occurences[*byte as usize] += 1;
-                    }
-                    total_occurences += BUFFER_SIZE as u128;
+        if chunk_len < STREAM_CHUNK {
+            break;
+        }
+    }
 
-                    cum_occurences = occurences.iter().scan(0, |cum_occ, &occ| {
-                        let old_cum_occ = *cum_occ;
-                        *cum_occ += occ;
-                        return Some(Endpoints{left: old_cum_occ, right: *cum_occ});
-                    }).collect();
+    let mut checksum = [0u8; 4];
+    for byte in &mut checksum {
+        *byte = decode_uniform(&mut coder, 256)? as u8;
+    }
+    header.checksum = u32::from_le_bytes(checksum);
 
-                    if occurences.iter().sum::<u128>() > total_occurences {
-                        panic!("invalid number of total occurences");
-                    }
-                }
+    out.flush()?;
+    return Ok(hasher.finalize());
+}
 
-                // divide the (total) subinterval into subsubintervals
+fn decode_with_coder<R: Read, W: Write, M: Model>(input: &mut R, out: &mut W, model: &mut M, header: &mut Header) -> Result<u32, ArithmeticError> {
+    return match header.coder {
+        CoderType::BIT => decode_with(out, model, BitDecoder::new(input)?, header),
+        CoderType::RANGE => decode_with(out, model, RangeDecoder::new(input)?, header),
+    };
+}
 
-                let interval_len = interval.right - interval.left;
-                possible_bytes = cum_occurences.iter().enumerate().map(|(byte, cum_occ)| (byte as u8, Endpoints{left: interval.left + interval_len * cum_occ.left / total_occurences, right: interval.left + interval_len * cum_occ.right / total_occurences})).collect();
+// the model, its options and the coder are all read from the header
 
-                // retain only the bytes whose intervals overlap with the tag interval
+pub fn decode<R: Read, W: Write>(input: &mut R, out: &mut W) -> Result<Header, ArithmeticError> {
 
-                possible_bytes.retain(|(_, byte_interval)| byte_interval.left < tag_interval.right && byte_interval.right > tag_interval.left);
+    let mut header = Header::read(input)?;
+    let options = header.options;
 
-            }
-        }
-    }
+    let checksum = match header.model {
+        ModelType::ORDER0 => decode_with_coder(input, out, &mut Order0Model::new(options), &mut header),
+        ModelType::ORDER1 => decode_with_coder(input, out, &mut ContextModel::new(1, options), &mut header),
+        ModelType::ORDER2 => decode_with_coder(input, out, &mut ContextModel::new(2, options), &mut header),
+        ModelType::STATIC => {
+            let mut static_model = StaticModel::read_table(input)?;
+            decode_with_coder(input, out, &mut static_model, &mut header)
+        },
+    }?;
 
-    if exit_code == 1 {
-        return Err("failed to decode the text".try_into()?);
+    if checksum != header.checksum {
+        return Err(ArithmeticError::ChecksumMismatch{expected: header.checksum, actual: checksum});
     }
 
-    out.write(&decoded).expect("can't write decoded text to the specified file");
-
-    Ok(())
+    return Ok(header);
 }
@@ -1,11 +1,20 @@
-use std::collections::VecDeque;
-use std::error::Error;
-use std::fs::File;
+use std::io;
 use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
 use std::io::Write;
+use std::time::Instant;
+use crc32fast::Hasher;
+use entropy::accumulator::EntropyAccumulator;
+use entropy::stats::CompressionStats;
+use crate::bits::BitWriter;
+use crate::coder::{check_total, encode_uniform, CoderType, Encoder};
+use crate::container::{Header, STREAM_CHUNK};
+use crate::error::ArithmeticError;
+use crate::model::*;
+use crate::range_coder::RangeEncoder;
 
 const BUFFER_SIZE: usize = 256;
-const BYTES_RANGE: usize = 256;
 const MAX_HIGH: u128 = 0x00000001000000000000000000000000;
 const MIN_LOW: u128 = 0x00000000000000000000000000000000;
 const INTERVAL_BITS: u32 = 128 - MAX_HIGH.leading_zeros() - 1;
@@ -15,141 +24,134 @@ struct Endpoints {
     right: u128,
 }
 
-fn int_to_bin(n: u128) -> Vec<char> {
-    let mut bin_rep = Vec::new();
-    let mut bin_value = 0;
-    let mut two = 2u128.pow(INTERVAL_BITS - 1);
-    for _ in 0..INTERVAL_BITS {
-        if n >= bin_value + two {
-            bin_value += two;
-            bin_rep.push('1');
-        } else {
-            bin_rep.push('0');
+/* bit-level arithmetic encoder with E1/E2/E3 scaling */
+
+pub struct BitEncoder<W: Write> {
+    interval: Endpoints,
+    counter: u64,
+    coded: BitWriter<W>,
+}
+
+impl<W: Write> BitEncoder<W> {
+    pub fn new(out: W) -> Self {
+        Self {
+            interval: Endpoints{left: MIN_LOW, right: MAX_HIGH},
+            counter: 0,
+            coded: BitWriter::new(out),
         }
-        two /= 2;
     }
-    return bin_rep;
 }
 
-pub fn encode(file: &mut File, out: &mut File) -> Result<(u128, f64, f64, f64), Box<dyn Error>> {
-
-    out.write(&(file.metadata().unwrap().len() as u128).to_le_bytes())?;
-    out.write(&[0xA])?;
-    let mut buf = vec![0u8; BUFFER_SIZE];
-
-    let mut occurences: Vec<u128> = Vec::from([1u128; BYTES_RANGE]);
-    let mut cum_occurences: Vec<Endpoints>;
-    let mut total_occurences = BYTES_RANGE as u128;
-
-    let mut interval = Endpoints{left: MIN_LOW, right: MAX_HIGH};
+impl<W: Write> Encoder for BitEncoder<W> {
+    fn encode(&mut self, cum_left: u64, cum_right: u64, total: u64) -> Result<(), ArithmeticError> {
+        check_total(total)?;
+        let interval = &mut self.interval;
 
-    /* read the file by chunks and apply arithmetic coding to them */
+        // compute the subinterval
 
-    let mut coded = VecDeque::new();
-    let mut coded_len = 0;
-    
-    let mut counter = 0;
+        let interval_len = interval.right - interval.left;
+        (interval.left, interval.right) = (interval.left + interval_len * cum_left as u128 / total as u128, interval.left + interval_len * cum_right as u128 / total as u128);
 
-    loop {
+        // perform scaling if needed
 
-        /* compute the cumulative occurences sum for each byte */
+        loop {
+            if interval.right <= MAX_HIGH / 2 {
 
-        cum_occurences = occurences.iter().scan(0, |cum_occ, &occ| {
-            let old_cum_occ = *cum_occ;
-            *cum_occ += occ;
-            return Some(Endpoints{left: old_cum_occ, right: *cum_occ});
-        }).collect();
+                // scale right
 
+                interval.left = 2 * interval.left;
+                interval.right = 2 * interval.right;
 
-        /* read the next chunk of bytes */
+                // append 0 and {counter} 1s to the code
 
-        let bytes_read = file.read(&mut buf)?;
+                self.coded.write_bit(false)?;
+                self.coded.write_bits(true, self.counter)?;
 
-        /* find subintervals for each byte in the chunk */
+                // reset the counter
 
-        for i in 0..bytes_read {
+                self.counter = 0;
 
-            let byte = &buf[i];
+            } else if interval.left >= MAX_HIGH / 2 {
 
-            // compute the subinterval
+                // scale left
 
-            let interval_len = interval.right - interval.left;
-            (interval.left, interval.right) = (interval.left + interval_len * cum_occurences[*byte as usize].left / total_occurences, interval.left + interval_len * cum_occurences[*byte as usize].right / total_occurences);
+                interval.left = 2 * interval.left - MAX_HIGH;
+                interval.right = 2 * interval.right - MAX_HIGH;
 
-            // perform scaling if needed
+                // append 1 and {counter} 0s to the code
 
-            loop {
-                if interval.right <= MAX_HIGH / 2 {
+                self.coded.write_bit(true)?;
+                self.coded.write_bits(false, self.counter)?;
 
-                    // scale right
+                // reset the counter
 
-                    interval.left = 2 * interval.left;
-                    interval.right = 2 * interval.right;
+                self.counter = 0;
 
-                    // append 0 and {counter} 1s to the code
+            } else if interval.left >= MAX_HIGH / 4 && interval.right <= MAX_HIGH * 3 / 4 {
 
-                    coded.push_back('0');
-                    for _ in 0..counter {
-                        coded.push_back('1');
-                    }
-                    coded_len += 1 + counter;
+                // scale both ways
 
-                    // reset the counter
+                interval.left = 2 * interval.left - MAX_HIGH / 2;
+                interval.right = 2 * interval.right - MAX_HIGH / 2;
 
-                    counter = 0;
+                // increment the counter
 
-                } else if interval.left >= MAX_HIGH / 2 {
+                self.counter += 1;
 
-                    // scale left
+            } else {
 
-                    interval.left = 2 * interval.left - MAX_HIGH;
-                    interval.right = 2 * interval.right - MAX_HIGH;
+                // can't scale further; quit the loop
 
-                    // append 1 and {counter} 0s to the code
+                break;
+            }
+        }
 
-                    coded.push_back('1');
-                    for _ in 0..counter {
-                        coded.push_back('0');
-                    }
-                    coded_len += 1 + counter;
+        return Ok(());
+    }
 
-                    // reset the counter
+    fn finish(mut self) -> Result<u64, ArithmeticError> {
 
-                    counter = 0;
+        // the pending bits follow the first bit of the tag
 
-                } else if interval.left >= MAX_HIGH / 4 && interval.right <= MAX_HIGH * 3 / 4 {
-                    
-                    // scale both ways
+        let binary_tag = self.interval.left + (self.interval.right - self.interval.left) / 2;
+        let first_bit = (binary_tag >> (INTERVAL_BITS - 1)) & 1 == 1;
+        self.coded.write_bit(first_bit)?;
+        self.coded.write_bits(!first_bit, self.counter)?;
+        for pos in (0..(INTERVAL_BITS - 1)).rev() {
+            self.coded.write_bit((binary_tag >> pos) & 1 == 1)?;
+        }
 
-                    interval.left = 2 * interval.left - MAX_HIGH / 2;
-                    interval.right = 2 * interval.right - MAX_HIGH / 2;
+        let coded_len = self.coded.bits_written();
+        self.coded.finish()?;
+        return Ok(coded_len);
+    }
+}
 
-                    // increment the counter
+// read until the buffer is full or the input is exhausted
 
-                    counter += 1;
+fn read_chunk<R: Read>(input: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    let mut bytes_read = 0;
+    while bytes_read < buf.len() {
+        match input.read(&mut buf[bytes_read..]) {
+            Ok(0) => break,
+            Ok(n) => bytes_read += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    return Ok(bytes_read);
+}
 
-                } else {
-                    
-                    // can't scale further; quit the loop
+// returns the number of bytes written
 
-                    break;
-                }
+fn encode_with<R: Read, M: Model, E: Encoder>(input: &mut R, model: &mut M, mut coder: E) -> Result<u64, ArithmeticError> {
 
-                while coded.len() >= 8 {
-                    let coded_byte_seq = coded.drain(..8);
-                    let byte_value: u8 = coded_byte_seq.fold(0, |acc, digit| (acc << 1) + digit.to_digit(2).unwrap() as u8);
-                    out.write(&[byte_value]).expect("can't write code to the specified file");
-                }
-            }
-        }
+    let mut buf = vec![0u8; BUFFER_SIZE];
 
-        /* update the occurences of each byte based on the chunk */
+    /* read the file by chunks and apply arithmetic coding to them */
 
-        for i in 0..bytes_read {
-            let byte = &buf[i];
-            occurences[*byte as usize] += 1;
-        }
-        total_occurences += bytes_read as u128;
+    loop {
+        let bytes_read = read_chunk(input, &mut buf)?;
 
         if bytes_read == 0 {
 
@@ -157,45 +159,170 @@ pub fn encode(file: &mut File, out: &mut File) -> Result<(u128, f64, f64, f64),
 
             break;
         }
-    }
 
-    let binary_tag = int_to_bin(interval.left + (interval.right - interval.left) / 2);
-    for i in 0..binary_tag.len() {
-        coded.push_back(binary_tag[i]);
+        for byte in &buf[..bytes_read] {
+            model.encode(*byte, &mut coder)?;
+        }
     }
 
-    if coded.len() % 8 != 0 {
-        coded_len += 8 - (coded.len() & 8);
-        for _ in 0..8 - (coded.len() % 8) {
-            coded.push_back('0');
+    let coded_len = coder.finish()?;
+    return Ok(coded_len.div_ceil(8));
+}
+
+fn encode_with_coder<R: Read, W: Write, M: Model>(input: &mut R, out: &mut W, model: &mut M, coder: &CoderType) -> Result<u64, ArithmeticError> {
+    return match coder {
+        CoderType::BIT => encode_with(input, model, BitEncoder::new(out)),
+        CoderType::RANGE => encode_with(input, model, RangeEncoder::new(out)),
+    };
+}
+
+// returns the number of bytes written
+
+fn encode_stream_with<R: Read, M: Model, E: Encoder>(input: &mut R, model: &mut M, mut coder: E, histogram: &mut EntropyAccumulator) -> Result<u64, ArithmeticError> {
+
+    let mut buf = vec![0u8; STREAM_CHUNK as usize];
+    let mut hasher = Hasher::new();
+
+    /* code every chunk after its length; the first one shorter than the others ends the text */
+
+    loop {
+        let bytes_read = read_chunk(input, &mut buf)?;
+        encode_uniform(&mut coder, bytes_read as u64, STREAM_CHUNK + 1)?;
+
+        for byte in &buf[..bytes_read] {
+            model.encode(*byte, &mut coder)?;
+        }
+        hasher.update(&buf[..bytes_read]);
+        histogram.update(&buf[..bytes_read]);
+
+        if bytes_read < buf.len() {
+            break;
         }
     }
 
-    while coded.len() >= 8 {
-        let coded_byte_seq = coded.drain(..8);
-        let byte_value: u8 = coded_byte_seq.fold(0, |acc, digit| (acc << 1) + digit.to_digit(2).unwrap() as u8);
-        out.write(&[byte_value]).expect("can't write code to the specified file");
+    // the checksum closes the code
+
+    for byte in hasher.finalize().to_le_bytes() {
+        encode_uniform(&mut coder, byte as u64, 256)?;
     }
 
-    let text_len = total_occurences - BYTES_RANGE as u128;
+    let coded_len = coder.finish()?;
+    return Ok(coded_len.div_ceil(8));
+}
 
-    // remove the "extra" occurences
+fn encode_stream_with_coder<R: Read, W: Write, M: Model>(input: &mut R, out: &mut W, model: &mut M, coder: &CoderType, histogram: &mut EntropyAccumulator) -> Result<u64, ArithmeticError> {
+    return match coder {
+        CoderType::BIT => encode_stream_with(input, model, BitEncoder::new(out), histogram),
+        CoderType::RANGE => encode_stream_with(input, model, RangeEncoder::new(out), histogram),
+    };
+}
 
-    for i in 0..occurences.len() {
-        occurences[i] -= 1;
-    }
-    total_occurences -= BYTES_RANGE as u128;
+// a stream which can't be rewound, e.g. the standard input, is coded in a single pass with its length
+// and checksum after the code; the static model needs two passes, so it is left to encode_seekable
+
+pub fn encode<R: Read, W: Write>(input: &mut R, out: &mut W, model: &ModelType, options: &ModelOptions, coder: &CoderType) -> Result<CompressionStats, ArithmeticError> {
+    options.validate()?;
+
+    let start_time = Instant::now();
+
+    let header = Header {
+        model: *model,
+        options: *options,
+        coder: *coder,
+        text_len: 0,
+        checksum: 0,
+        streamed: true,
+    };
+    let mut header_bytes = Vec::new();
+    header.write(&mut header_bytes)?;
+
+    let mut histogram = EntropyAccumulator::new();
+    let coded_len = match model {
+        ModelType::ORDER0 => {
+            out.write_all(&header_bytes)?;
+            encode_stream_with_coder(input, out, &mut Order0Model::new(*options), coder, &mut histogram)?
+        },
+        ModelType::ORDER1 => {
+            out.write_all(&header_bytes)?;
+            encode_stream_with_coder(input, out, &mut ContextModel::new(1, *options), coder, &mut histogram)?
+        },
+        ModelType::ORDER2 => {
+            out.write_all(&header_bytes)?;
+            encode_stream_with_coder(input, out, &mut ContextModel::new(2, *options), coder, &mut histogram)?
+        },
+        ModelType::STATIC => return Err(ArithmeticError::UnseekableInput),
+    };
+
+    return Ok(CompressionStats::new(
+        histogram.total_length() as u64,
+        header_bytes.len() as u64 + coded_len,
+        histogram.entropy(),
+        start_time.elapsed(),
+    ));
+}
 
-    // compute the entropy
+// the input is read twice, first for the header and then for the code, and left at its end
 
-    let mut entropy = 0.0;
-    for byte in 0..BYTES_RANGE {
-        let occurence_pbb: f64 = occurences[byte] as f64 / total_occurences as f64;
-        if occurence_pbb > 0.0 {
-            entropy += -occurence_pbb * f64::log2(occurence_pbb);
-        }
-    } 
+pub fn encode_seekable<R: Read + Seek, W: Write>(input: &mut R, out: &mut W, model: &ModelType, options: &ModelOptions, coder: &CoderType) -> Result<CompressionStats, ArithmeticError> {
+    options.validate()?;
+
+    let start_time = Instant::now();
+
+    // the first pass finds the text length, checksum and occurences for the header
 
-    let compression_rate = coded_len as f64 / text_len as f64 / 8.0;
-    return Ok((text_len, entropy, compression_rate * 8.0, compression_rate));
+    let start = input.stream_position()?;
+    let mut buf = vec![0u8; BUFFER_SIZE];
+    let mut hasher = Hasher::new();
+    let mut histogram = EntropyAccumulator::new();
+    loop {
+        let bytes_read = read_chunk(input, &mut buf)?;
+        if bytes_read == 0 {
+            break;
+        }
+        hasher.update(&buf[..bytes_read]);
+        histogram.update(&buf[..bytes_read]);
+    }
+    input.seek(SeekFrom::Start(start))?;
+
+    let header = Header {
+        model: *model,
+        options: *options,
+        coder: *coder,
+        text_len: histogram.total_length() as u64,
+        checksum: hasher.finalize(),
+        streamed: false,
+    };
+    let mut header_bytes = Vec::new();
+    header.write(&mut header_bytes)?;
+
+    let coded_len = match model {
+        ModelType::ORDER0 => {
+            out.write_all(&header_bytes)?;
+            encode_with_coder(input, out, &mut Order0Model::new(*options), coder)?
+        },
+        ModelType::ORDER1 => {
+            out.write_all(&header_bytes)?;
+            encode_with_coder(input, out, &mut ContextModel::new(1, *options), coder)?
+        },
+        ModelType::ORDER2 => {
+            out.write_all(&header_bytes)?;
+            encode_with_coder(input, out, &mut ContextModel::new(2, *options), coder)?
+        },
+        ModelType::STATIC => {
+
+            // store the occurences after the header
+
+            let mut static_model = StaticModel::new(histogram.occurences());
+            static_model.write_table(&mut header_bytes)?;
+            out.write_all(&header_bytes)?;
+            encode_with_coder(input, out, &mut static_model, coder)?
+        },
+    };
+
+    return Ok(CompressionStats::new(
+        header.text_len,
+        header_bytes.len() as u64 + coded_len,
+        histogram.entropy(),
+        start_time.elapsed(),
+    ));
 }
@@ -0,0 +1,102 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+// what is wrong with the header of the coded file
+
+#[derive(Debug)]
+#[derive(PartialEq)]
+pub enum HeaderError {
+    BadMagic,
+    UnsupportedVersion(u8),
+    UnknownModel(u8),
+    UnknownCoder(u8),
+    InvalidOptions,
+    InvalidVarint,
+}
+
+impl fmt::Display for HeaderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            HeaderError::BadMagic => write!(f, "not an arithmetic coded file"),
+            HeaderError::UnsupportedVersion(version) => write!(f, "unsupported format version {}", version),
+            HeaderError::UnknownModel(id) => write!(f, "unknown model identifier {}", id),
+            HeaderError::UnknownCoder(id) => write!(f, "unknown coder identifier {}", id),
+            HeaderError::InvalidOptions => write!(f, "invalid model options"),
+            HeaderError::InvalidVarint => write!(f, "invalid varint"),
+        };
+    }
+}
+
+/* errors of encoding and decoding; corrupted input is reported here and never panics */
+
+#[derive(Debug)]
+pub enum ArithmeticError {
+
+    // the input ended before the header or the code was complete
+
+    TruncatedInput,
+    BadHeader(HeaderError),
+
+    // the model's total of occurences is zero or exceeds the coder's precision
+
+    ModelOverflow,
+
+    // the increment of the model is zero or exceeds its limit
+
+    InvalidOptions,
+
+    // the code points outside of the current interval
+
+    CorruptedCode,
+
+    // the input to encode changed between the passes
+
+    UnexpectedSymbol(u8),
+
+    // the static model reads the text twice, which a stream can't be
+
+    UnseekableInput,
+    ChecksumMismatch{expected: u32, actual: u32},
+    Io(io::Error),
+}
+
+impl fmt::Display for ArithmeticError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            ArithmeticError::TruncatedInput => write!(f, "the coded input is truncated"),
+            ArithmeticError::BadHeader(e) => write!(f, "bad header of the coded file: {}", e),
+            ArithmeticError::ModelOverflow => write!(f, "the model's occurences exceed the coder's precision"),
+            ArithmeticError::InvalidOptions => write!(f, "invalid model options: the increment should be positive and at most the limit"),
+            ArithmeticError::CorruptedCode => write!(f, "failed to decode the text: the code is corrupted"),
+            ArithmeticError::UnexpectedSymbol(byte) => write!(f, "the byte {:#04x} is absent from the frequency table", byte),
+            ArithmeticError::UnseekableInput => write!(f, "the static model can't read its input twice"),
+            ArithmeticError::ChecksumMismatch{expected, actual} => write!(f, "checksum mismatch: expected {:08x}, got {:08x}", expected, actual),
+            ArithmeticError::Io(e) => write!(f, "{}", e),
+        };
+    }
+}
+
+impl Error for ArithmeticError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        return match self {
+            ArithmeticError::Io(e) => Some(e),
+            _ => None,
+        };
+    }
+}
+
+impl From<io::Error> for ArithmeticError {
+    fn from(e: io::Error) -> Self {
+        return match e.kind() {
+            io::ErrorKind::UnexpectedEof => ArithmeticError::TruncatedInput,
+            _ => ArithmeticError::Io(e),
+        };
+    }
+}
+
+impl From<HeaderError> for ArithmeticError {
+    fn from(e: HeaderError) -> Self {
+        return ArithmeticError::BadHeader(e);
+    }
+}
@@ -0,0 +1,90 @@
+// symbol occurences kept in a Fenwick tree, so that both cumulative sums
+// and the search for the symbol containing a cumulative value take O(log n)
+
+#[derive(Clone)]
+pub struct FrequencyTable {
+    tree: Vec<u64>,
+    total: u64,
+}
+
+impl FrequencyTable {
+
+    // create a table of {no_symbols} symbols, each occuring {initial} times
+
+    pub fn new(no_symbols: usize, initial: u64) -> Self {
+        let mut table = Self {
+            tree: vec![0; no_symbols + 1],
+            total: 0,
+        };
+        for symbol in 0..no_symbols {
+            table.add(symbol, initial);
+        }
+        return table;
+    }
+
+    pub fn total(&self) -> u64 {
+        return self.total;
+    }
+
+    pub fn add(&mut self, symbol: usize, delta: u64) {
+        let mut idx = symbol + 1;
+        while idx < self.tree.len() {
+            self.tree[idx] += delta;
+            idx += idx & idx.wrapping_neg();
+        }
+        self.total += delta;
+    }
+
+    // the sum of occurences of all the symbols preceding {symbol}
+
+    pub fn cumulative(&self, symbol: usize) -> u64 {
+        let mut sum = 0;
+        let mut idx = symbol;
+        while idx > 0 {
+            sum += self.tree[idx];
+            idx -= idx & idx.wrapping_neg();
+        }
+        return sum;
+    }
+
+    pub fn occurences(&self, symbol: usize) -> u64 {
+        return self.cumulative(symbol + 1) - self.cumulative(symbol);
+    }
+
+    // the cumulative interval [left, right) of the symbol
+
+    pub fn interval(&self, symbol: usize) -> (u64, u64) {
+        let left = self.cumulative(symbol);
+        return (left, left + self.occurences(symbol));
+    }
+
+    // halve the occurences of every symbol, keeping the symbols which occured at least once possible
+
+    pub fn halve(&mut self) {
+        let halved: Vec<u64> = (0..(self.tree.len() - 1))
+            .map(|symbol| self.occurences(symbol).div_ceil(2))
+            .collect();
+
+        self.tree.iter_mut().for_each(|node| *node = 0);
+        self.total = 0;
+        for (symbol, occ) in halved.into_iter().enumerate() {
+            self.add(symbol, occ);
+        }
+    }
+
+    // find the symbol whose cumulative interval contains {target}; target must be less than the total
+
+    pub fn find(&self, mut target: u64) -> usize {
+        let mut idx = 0;
+        let mut step = (self.tree.len() - 1).next_power_of_two();
+        while step > 0 {
+            let next = idx + step;
+            if next < self.tree.len() && self.tree[next] <= target {
+                idx = next;
+                target -= self.tree[next];
+            }
+            step /= 2;
+        }
+        return idx;
+    }
+}
@@ -0,0 +1,33 @@
+use std::io::Cursor;
+
+mod bits;
+mod frequencies;
+
+pub mod binary;
+pub mod coder;
+pub mod container;
+pub mod error;
+pub mod model;
+pub mod range_coder;
+pub mod encoder;
+pub mod decoder;
+
+pub use binary::{BinaryDecoder, BinaryEncoder, BitModel};
+pub use coder::CoderType;
+pub use container::Header;
+pub use error::{ArithmeticError, HeaderError};
+pub use model::{ModelOptions, ModelType};
+pub use encoder::{encode, encode_seekable};
+pub use decoder::decode;
+
+pub fn encode_bytes(text: &[u8], model: &ModelType, options: &ModelOptions, coder: &CoderType) -> Result<Vec<u8>, ArithmeticError> {
+    let mut coded = Vec::new();
+    encode_seekable(&mut Cursor::new(text), &mut coded, model, options, coder)?;
+    return Ok(coded);
+}
+
+pub fn decode_bytes(coded: &[u8]) -> Result<Vec<u8>, ArithmeticError> {
+    let mut decoded = Vec::new();
+    decode(&mut &coded[..], &mut decoded)?;
+    return Ok(decoded);
+}
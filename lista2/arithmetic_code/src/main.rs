@@ -1,14 +1,60 @@
 use std::error::Error;
 use std::fs::File;
+use std::io;
+use arithmetic_code::*;
 
-mod encoder;
-mod decoder;
+fn parse_model(model: Option<&String>) -> Result<ModelType, Box<dyn Error>> {
+    return match model.map(|model| model.as_str()) {
+        None | Some("order0") => Ok(ModelType::ORDER0),
+        Some("order1") => Ok(ModelType::ORDER1),
+        Some("order2") => Ok(ModelType::ORDER2),
+        Some("static") => Ok(ModelType::STATIC),
+        _ => Err("Please specify a valid model: order0, order1, order2 or static".into()),
+    };
+}
+
+// split the arguments into the positional ones and the options given as --option value
+
+fn parse_options(args: &[String]) -> Result<(Vec<String>, ModelOptions, CoderType, bool), Box<dyn Error>> {
+    let mut positional = Vec::new();
+    let mut options = ModelOptions::default();
+    let mut coder = CoderType::BIT;
+    let mut json = false;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--increment" => {
+                let value = args.next().ok_or("Please specify the increment")?;
+                options.increment = value.parse()?;
+                if options.increment == 0 {
+                    return Err("The increment should be positive".into());
+                }
+            },
+            "--limit" => {
+                let value = args.next().ok_or("Please specify the limit of the total occurences")?;
+                options.limit = Some(value.parse()?);
+            },
+            "--coder" => {
+                coder = match args.next().map(|coder| coder.as_str()) {
+                    Some("bit") => CoderType::BIT,
+                    Some("range") => CoderType::RANGE,
+                    _ => return Err("Please specify a valid coder: bit or range".into()),
+                };
+            },
+            "--json" => json = true,
+            _ => positional.push(arg.clone()),
+        }
+    }
+
+    return Ok((positional, options, coder, json));
+}
 
 fn main() -> Result<(), Box<dyn Error>> {
 
     // read command line arguments
 
-    let args: Vec<String> = std::env::args().collect();
+    let (args, options, coder, json) = parse_options(&std::env::args().collect::<Vec<String>>())?;
 
     if args.len() < 2 {
         return Err("Please specify if you want to encode or decode a file".into());
@@ -19,20 +65,28 @@ fn main() -> Result<(), Box<dyn Error>> {
             return Err("Please specify the file to encode and the output file".into());
         }
 
-        let mut source_file = File::open(&args[2])?;
+        let model = parse_model(args.get(4))?;
         let mut coded_file = File::create(&args[3])?;
-        let (text_len, entropy, code_len, compression_rate) = encoder::encode(&mut source_file, &mut coded_file)?;
-        println!("text length = {}", text_len);
-        println!("entropy = {}", entropy);
-        println!("avg code length = {}", code_len);
-        println!("compression rate = {}", compression_rate);
+
+        // "-" reads the text from the standard input
+
+        let stats = match args[2].as_str() {
+            "-" => encode(&mut io::stdin().lock(), &mut coded_file, &model, &options, &coder)?,
+            path => encode_seekable(&mut File::open(path)?, &mut coded_file, &model, &options, &coder)?,
+        };
+        if json {
+            print!("{}", stats.to_json());
+        } else {
+            print!("{}", stats);
+        }
     } else if args[1] == "decode" {
         if args.len() < 4 {
             return Err("Please specify the file to decode and the output file".into());
         }
 
+        let mut coded_file = File::open(&args[2])?;
         let mut decoded_file = File::create(&args[3])?;
-        decoder::decode(&args[2], &mut decoded_file)?;
+        decode(&mut coded_file, &mut decoded_file)?;
     } else {
         return Err("Please specify what you want to do".into());
     }
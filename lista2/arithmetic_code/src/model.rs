@@ -0,0 +1,393 @@
+use std::collections::HashMap;
+use std::io;
+use std::io::Read;
+use std::io::Write;
+use crate::coder::{Decoder, Encoder};
+use crate::error::{ArithmeticError, HeaderError};
+use crate::frequencies::FrequencyTable;
+
+const BYTES_RANGE: usize = 256;
+
+// the coder's precision requires the total of the occurences to stay well below 2^32
+
+const MAX_TOTAL: u64 = 1 << 24;
+
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(Debug)]
+#[derive(PartialEq)]
+pub enum ModelType {
+    ORDER0,
+    ORDER1,
+    ORDER2,
+    STATIC,
+}
+
+// how fast the models adapt: every coded byte adds {increment} to its occurences,
+// and once the total exceeds {limit} all the occurences are halved, so that old data is gradually forgotten
+
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(Debug)]
+#[derive(PartialEq)]
+pub struct ModelOptions {
+    pub increment: u64,
+    pub limit: Option<u64>,
+}
+
+impl ModelOptions {
+    fn limit(&self) -> u64 {
+        return self.limit.unwrap_or(MAX_TOTAL).min(MAX_TOTAL);
+    }
+
+    // a zero increment leaves the coder's interval empty, so it could never be scaled
+
+    pub fn validate(&self) -> Result<(), ArithmeticError> {
+        if self.increment == 0 || self.increment > self.limit() {
+            return Err(ArithmeticError::InvalidOptions);
+        }
+        return Ok(());
+    }
+}
+
+impl Default for ModelOptions {
+    fn default() -> Self {
+        return Self {
+            increment: 1,
+            limit: None,
+        };
+    }
+}
+
+// a model assigns probabilities to the bytes and drives the entropy coder;
+// the decoder's model must see exactly the same bytes as the encoder's to stay in sync
+
+pub trait Model {
+    fn encode<E: Encoder>(&mut self, byte: u8, encoder: &mut E) -> Result<(), ArithmeticError>;
+    fn decode<D: Decoder>(&mut self, decoder: &mut D) -> Result<u8, ArithmeticError>;
+}
+
+/* adaptive order-0 model, updated after every byte */
+
+pub struct Order0Model {
+    occurences: FrequencyTable,
+    options: ModelOptions,
+}
+
+impl Order0Model {
+    pub fn new(options: ModelOptions) -> Self {
+        Self {
+            occurences: FrequencyTable::new(BYTES_RANGE, 1),
+            options,
+        }
+    }
+
+    fn update(&mut self, byte: u8) {
+        self.occurences.add(byte as usize, self.options.increment);
+        if self.occurences.total() > self.options.limit() {
+            self.occurences.halve();
+        }
+    }
+}
+
+impl Model for Order0Model {
+    fn encode<E: Encoder>(&mut self, byte: u8, encoder: &mut E) -> Result<(), ArithmeticError> {
+        let (cum_left, cum_right) = self.occurences.interval(byte as usize);
+        encoder.encode(cum_left, cum_right, self.occurences.total())?;
+        self.update(byte);
+        return Ok(());
+    }
+
+    fn decode<D: Decoder>(&mut self, decoder: &mut D) -> Result<u8, ArithmeticError> {
+        let target = decoder.target(self.occurences.total())?;
+        let byte = self.occurences.find(target);
+        let (cum_left, cum_right) = self.occurences.interval(byte);
+        decoder.consume(cum_left, cum_right, self.occurences.total())?;
+        self.update(byte as u8);
+        return Ok(byte as u8);
+    }
+}
+
+/* static order-0 model with the occurences counted beforehand and stored along with the code */
+
+pub struct StaticModel {
+    occurences: FrequencyTable,
+}
+
+pub(crate) fn write_varint<W: Write>(out: &mut W, mut value: u64) -> io::Result<()> {
+    while value >= 0x80 {
+        out.write_all(&[(value as u8 & 0x7F) | 0x80])?;
+        value >>= 7;
+    }
+    return out.write_all(&[value as u8]);
+}
+
+// a varint of more than 64 bits is invalid, its high bits aren't dropped
+
+pub(crate) fn read_varint<R: Read>(input: &mut R) -> Result<u64, ArithmeticError> {
+    let mut value = 0;
+    for shift in (0..64).step_by(7) {
+        let mut byte = [0u8; 1];
+        input.read_exact(&mut byte)?;
+        if shift == 63 && byte[0] & 0x7F > 1 {
+            return Err(HeaderError::InvalidVarint.into());
+        }
+        value |= ((byte[0] & 0x7F) as u64) << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    return Err(HeaderError::InvalidVarint.into());
+}
+
+impl StaticModel {
+
+    // scale the occurences down to the coder's precision, keeping every occuring byte possible
+
+    pub fn new(occurences: &[usize; BYTES_RANGE]) -> Self {
+        let total: u64 = occurences.iter().map(|&occ| occ as u64).sum();
+        let max_total = MAX_TOTAL - BYTES_RANGE as u64;
+
+        let mut table = FrequencyTable::new(BYTES_RANGE, 0);
+        for (byte, &occ) in occurences.iter().enumerate() {
+            let occ = occ as u64;
+            let scaled = if total > max_total && occ > 0 {
+                (occ as u128 * max_total as u128 / total as u128).max(1) as u64
+            } else {
+                occ
+            };
+            table.add(byte, scaled);
+        }
+
+        return Self {
+            occurences: table,
+        };
+    }
+
+    // the table is stored as a bitmap of the occuring bytes followed by their occurences as varints
+
+    pub fn write_table<W: Write>(&self, out: &mut W) -> Result<(), ArithmeticError> {
+        let mut bitmap = [0u8; BYTES_RANGE / 8];
+        for byte in 0..BYTES_RANGE {
+            if self.occurences.occurences(byte) > 0 {
+                bitmap[byte / 8] |= 1 << (byte % 8);
+            }
+        }
+        out.write_all(&bitmap)?;
+
+        for byte in 0..BYTES_RANGE {
+            let occ = self.occurences.occurences(byte);
+            if occ > 0 {
+                write_varint(out, occ)?;
+            }
+        }
+
+        return Ok(());
+    }
+
+    pub fn read_table<R: Read>(input: &mut R) -> Result<Self, ArithmeticError> {
+        let mut bitmap = [0u8; BYTES_RANGE / 8];
+        input.read_exact(&mut bitmap)?;
+
+        // the total is checked before every addition, so that a corrupted table can't overflow it
+
+        let mut table = FrequencyTable::new(BYTES_RANGE, 0);
+        for byte in 0..BYTES_RANGE {
+            if bitmap[byte / 8] & (1 << (byte % 8)) != 0 {
+                let occ = read_varint(input)?;
+                if occ > MAX_TOTAL || table.total().checked_add(occ).is_none_or(|total| total > MAX_TOTAL) {
+                    return Err(ArithmeticError::ModelOverflow);
+                }
+                table.add(byte, occ);
+            }
+        }
+
+        return Ok(Self {
+            occurences: table,
+        });
+    }
+}
+
+impl Model for StaticModel {
+    fn encode<E: Encoder>(&mut self, byte: u8, encoder: &mut E) -> Result<(), ArithmeticError> {
+        let (cum_left, cum_right) = self.occurences.interval(byte as usize);
+        if cum_left == cum_right {
+            return Err(ArithmeticError::UnexpectedSymbol(byte));
+        }
+        return encoder.encode(cum_left, cum_right, self.occurences.total());
+    }
+
+    fn decode<D: Decoder>(&mut self, decoder: &mut D) -> Result<u8, ArithmeticError> {
+        let target = decoder.target(self.occurences.total())?;
+        let byte = self.occurences.find(target);
+        let (cum_left, cum_right) = self.occurences.interval(byte);
+        decoder.consume(cum_left, cum_right, self.occurences.total())?;
+        return Ok(byte as u8);
+    }
+}
+
+/* the bytes seen so far after a single context, with the escape counted as the number of distinct bytes */
+
+#[derive(Default)]
+struct ContextStatistics {
+    occurences: Vec<(u8, u64)>,
+    total: u64,
+}
+
+impl ContextStatistics {
+    fn coding_total(&self) -> u64 {
+        return self.total + self.occurences.len() as u64;
+    }
+
+    fn interval(&self, byte: u8) -> Option<(u64, u64)> {
+        let mut cum_left = 0;
+        for &(symbol, occ) in &self.occurences {
+            if symbol == byte {
+                return Some((cum_left, cum_left + occ));
+            }
+            cum_left += occ;
+        }
+        return None;
+    }
+
+    fn escape_interval(&self) -> (u64, u64) {
+        return (self.total, self.coding_total());
+    }
+
+    // find the byte containing the target, along with its interval; None stands for the escape
+
+    fn find(&self, target: u64) -> Option<(u8, u64, u64)> {
+        let mut cum_left = 0;
+        for &(symbol, occ) in &self.occurences {
+            if target < cum_left + occ {
+                return Some((symbol, cum_left, cum_left + occ));
+            }
+            cum_left += occ;
+        }
+        return None;
+    }
+
+    fn add(&mut self, byte: u8, increment: u64) {
+        match self.occurences.iter_mut().find(|(symbol, _)| *symbol == byte) {
+            Some((_, occ)) => *occ += increment,
+            None => self.occurences.push((byte, increment)),
+        }
+        self.total += increment;
+    }
+
+    fn halve(&mut self) {
+        for (_, occ) in self.occurences.iter_mut() {
+            *occ = occ.div_ceil(2);
+        }
+        self.total = self.occurences.iter().map(|(_, occ)| occ).sum();
+    }
+}
+
+/* PPM-style model: the byte is coded in the longest context it has been seen in,
+   escaping to shorter contexts and eventually to an order-0 model where every byte is possible */
+
+pub struct ContextModel {
+    order: usize,
+    contexts: Vec<HashMap<u64, ContextStatistics>>,
+    order0: Order0Model,
+    history: u64,
+    options: ModelOptions,
+}
+
+impl ContextModel {
+
+    // the bytes before the beginning of the text are assumed to be zeros
+
+    pub fn new(order: usize, options: ModelOptions) -> Self {
+        assert!(order < 8, "the context model supports orders up to 7");
+        Self {
+            order,
+            contexts: (0..order).map(|_| HashMap::new()).collect(),
+            order0: Order0Model::new(options),
+            history: 0,
+            options,
+        }
+    }
+
+    fn context(&self, order: usize) -> u64 {
+        return self.history & ((1u64 << (8 * order)) - 1);
+    }
+
+    fn update(&mut self, byte: u8) {
+        for order in 1..=self.order {
+            let context = self.context(order);
+            let statistics = self.contexts[order - 1]
+                .entry(context)
+                .or_default();
+            statistics.add(byte, self.options.increment);
+            if statistics.coding_total() > self.options.limit() {
+                statistics.halve();
+            }
+        }
+        self.order0.update(byte);
+        self.history = (self.history << 8) | byte as u64;
+    }
+}
+
+impl Model for ContextModel {
+    fn encode<E: Encoder>(&mut self, byte: u8, encoder: &mut E) -> Result<(), ArithmeticError> {
+        let mut coded = false;
+
+        for order in (1..=self.order).rev() {
+            if let Some(statistics) = self.contexts[order - 1].get(&self.context(order)) {
+                if let Some((cum_left, cum_right)) = statistics.interval(byte) {
+                    encoder.encode(cum_left, cum_right, statistics.coding_total())?;
+                    coded = true;
+                    break;
+                }
+
+                // the byte hasn't been seen in this context; escape to a shorter one
+
+                let (cum_left, cum_right) = statistics.escape_interval();
+                encoder.encode(cum_left, cum_right, statistics.coding_total())?;
+            }
+        }
+
+        if !coded {
+            let occurences = &self.order0.occurences;
+            let (cum_left, cum_right) = occurences.interval(byte as usize);
+            encoder.encode(cum_left, cum_right, occurences.total())?;
+        }
+
+        self.update(byte);
+        return Ok(());
+    }
+
+    fn decode<D: Decoder>(&mut self, decoder: &mut D) -> Result<u8, ArithmeticError> {
+        let mut decoded = None;
+
+        for order in (1..=self.order).rev() {
+            if let Some(statistics) = self.contexts[order - 1].get(&self.context(order)) {
+                let target = decoder.target(statistics.coding_total())?;
+                if let Some((byte, cum_left, cum_right)) = statistics.find(target) {
+                    decoder.consume(cum_left, cum_right, statistics.coding_total())?;
+                    decoded = Some(byte);
+                    break;
+                }
+
+                let (cum_left, cum_right) = statistics.escape_interval();
+                decoder.consume(cum_left, cum_right, statistics.coding_total())?;
+            }
+        }
+
+        let byte = match decoded {
+            Some(byte) => byte,
+            None => {
+                let occurences = &self.order0.occurences;
+                let target = decoder.target(occurences.total())?;
+                let byte = occurences.find(target);
+                let (cum_left, cum_right) = occurences.interval(byte);
+                decoder.consume(cum_left, cum_right, occurences.total())?;
+                byte as u8
+            },
+        };
+
+        self.update(byte);
+        return Ok(byte);
+    }
+}
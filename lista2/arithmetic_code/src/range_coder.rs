@@ -0,0 +1,129 @@
+use std::io;
+use std::io::BufReader;
+use std::io::BufWriter;
+use std::io::Read;
+use std::io::Write;
+use crate::coder::{check_total, Decoder, Encoder};
+use crate::error::ArithmeticError;
+
+// 64-bit carry-less range coder (Subbotin); the bytes are output once the top byte of the interval settles,
+// and the range is cut whenever it becomes too small while straddling a byte boundary
+
+const TOP: u64 = 1 << 56;
+const BOT: u64 = 1 << 48;
+const CODE_BYTES: usize = 8;
+
+pub struct RangeEncoder<W: Write> {
+    low: u64,
+    range: u64,
+    out: BufWriter<W>,
+    bytes_written: u64,
+}
+
+impl<W: Write> RangeEncoder<W> {
+    pub fn new(out: W) -> Self {
+        Self {
+            low: 0,
+            range: u64::MAX,
+            out: BufWriter::new(out),
+            bytes_written: 0,
+        }
+    }
+
+    fn normalize(&mut self) -> io::Result<()> {
+        loop {
+            if (self.low ^ self.low.wrapping_add(self.range)) >= TOP {
+                if self.range >= BOT {
+                    break;
+                }
+                self.range = self.low.wrapping_neg() & (BOT - 1);
+            }
+            self.out.write_all(&[(self.low >> 56) as u8])?;
+            self.bytes_written += 1;
+            self.low <<= 8;
+            self.range <<= 8;
+        }
+        return Ok(());
+    }
+}
+
+impl<W: Write> Encoder for RangeEncoder<W> {
+    fn encode(&mut self, cum_left: u64, cum_right: u64, total: u64) -> Result<(), ArithmeticError> {
+        check_total(total)?;
+        let range = self.range / total;
+        self.low = self.low.wrapping_add(range * cum_left);
+        self.range = range * (cum_right - cum_left);
+        self.normalize()?;
+        return Ok(());
+    }
+
+    fn finish(mut self) -> Result<u64, ArithmeticError> {
+        self.out.write_all(&self.low.to_be_bytes())?;
+        self.out.flush()?;
+        return Ok((self.bytes_written + CODE_BYTES as u64) * 8);
+    }
+}
+
+pub struct RangeDecoder<R: Read> {
+    low: u64,
+    range: u64,
+    code: u64,
+    input: BufReader<R>,
+}
+
+impl<R: Read> RangeDecoder<R> {
+
+    // the encoder flushes all the bytes of {low}, so the decoder never reads past the code unless it is truncated
+
+    pub fn new(input: R) -> Result<Self, ArithmeticError> {
+        let mut decoder = Self {
+            low: 0,
+            range: u64::MAX,
+            code: 0,
+            input: BufReader::new(input),
+        };
+        for _ in 0..CODE_BYTES {
+            decoder.code = (decoder.code << 8) | decoder.read_byte()? as u64;
+        }
+        return Ok(decoder);
+    }
+
+    fn read_byte(&mut self) -> Result<u8, ArithmeticError> {
+        let mut byte = [0u8; 1];
+        self.input.read_exact(&mut byte)?;
+        return Ok(byte[0]);
+    }
+}
+
+impl<R: Read> Decoder for RangeDecoder<R> {
+    fn target(&mut self, total: u64) -> Result<u64, ArithmeticError> {
+        check_total(total)?;
+
+        let target = self.code.wrapping_sub(self.low) / (self.range / total);
+        if target >= total {
+            return Err(ArithmeticError::CorruptedCode);
+        }
+        return Ok(target);
+    }
+
+    fn consume(&mut self, cum_left: u64, cum_right: u64, total: u64) -> Result<(), ArithmeticError> {
+        check_total(total)?;
+        let range = self.range / total;
+        self.low = self.low.wrapping_add(range * cum_left);
+        self.range = range * (cum_right - cum_left);
+
+        loop {
+            if (self.low ^ self.low.wrapping_add(self.range)) >= TOP {
+                if self.range >= BOT {
+                    break;
+                }
+                self.range = self.low.wrapping_neg() & (BOT - 1);
+            }
+            self.code = (self.code << 8) | self.read_byte()? as u64;
+            self.low <<= 8;
+            self.range <<= 8;
+        }
+
+        return Ok(());
+    }
+}
@@ -0,0 +1,80 @@
+use arithmetic_code::*;
+use arithmetic_code::binary::*;
+
+fn pseudo_random_bits(len: usize, one_in: u32) -> Vec<bool> {
+    let mut seed: u32 = 2024;
+    return (0..len).map(|_| {
+        seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+        (seed >> 16) % one_in == 0
+    }).collect();
+}
+
+#[test]
+fn round_trip_bits() {
+    for bits in [vec![], vec![true], vec![false; 1000], pseudo_random_bits(5000, 2), pseudo_random_bits(5000, 13)] {
+        let coded = encode_bits(bits.iter().copied()).unwrap();
+        assert_eq!(decode_bits(&coded, bits.len()).unwrap(), bits);
+    }
+}
+
+#[test]
+fn skewed_bits_compress_close_to_entropy() {
+    let bits = pseudo_random_bits(100000, 20);
+    let coded = encode_bits(bits.iter().copied()).unwrap();
+
+    let p: f64 = 1.0 / 20.0;
+    let entropy = -p * p.log2() - (1.0 - p) * (1.0 - p).log2();
+    let coded_bits = coded.len() as f64 * 8.0;
+    assert!(coded_bits < entropy * bits.len() as f64 * 1.05);
+}
+
+#[test]
+fn round_trip_bit_planes() {
+    let residuals: Vec<u8> = (0..4000u32).map(|i| ((i * 37 % 11) as i8 - 5) as u8).collect();
+    let coded = encode_bit_planes(&residuals).unwrap();
+    assert!(coded.len() < residuals.len() / 2);
+    assert_eq!(decode_bit_planes(&coded, residuals.len()).unwrap(), residuals);
+
+    let all_bytes: Vec<u8> = (0..=255).collect();
+    let coded = encode_bit_planes(&all_bytes).unwrap();
+    assert_eq!(decode_bit_planes(&coded, all_bytes.len()).unwrap(), all_bytes);
+}
+
+#[test]
+fn caller_chosen_contexts() {
+    let bits = pseudo_random_bits(3000, 3);
+    let mut coded = Vec::new();
+
+    let mut encoder = BinaryEncoder::new(&mut coded);
+    let mut models = [BitModel::new(), BitModel::with_shift(3)];
+    for (idx, bit) in bits.iter().enumerate() {
+        encoder.encode(*bit, &mut models[idx % 2]).unwrap();
+    }
+    encoder.finish().unwrap();
+
+    let mut decoder = BinaryDecoder::new(&coded[..]).unwrap();
+    let mut models = [BitModel::new(), BitModel::with_shift(3)];
+    for (idx, bit) in bits.iter().enumerate() {
+        assert_eq!(decoder.decode(&mut models[idx % 2]).unwrap(), *bit);
+    }
+}
+
+#[test]
+fn model_adapts_to_the_bits() {
+    let mut model = BitModel::new();
+    assert_eq!(model.zero_probability(), 0.5);
+
+    let mut coded = Vec::new();
+    let mut encoder = BinaryEncoder::new(&mut coded);
+    for _ in 0..1000 {
+        encoder.encode(true, &mut model).unwrap();
+    }
+    assert!(model.zero_probability() > 0.0 && model.zero_probability() < 0.01);
+}
+
+#[test]
+fn truncated_bits_are_reported() {
+    let bits = pseudo_random_bits(5000, 2);
+    let coded = encode_bits(bits.iter().copied()).unwrap();
+    assert!(matches!(decode_bits(&coded[..coded.len() - 1], bits.len()), Err(ArithmeticError::TruncatedInput)));
+}
@@ -0,0 +1,202 @@
+use arithmetic_code::*;
+
+fn coded_text(model: ModelType, coder: CoderType) -> (Vec<u8>, Vec<u8>) {
+    let mut seed: u32 = 12345;
+    let text: Vec<u8> = (0..2000).map(|_| {
+        seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+        b"container "[(seed >> 16) as usize % 10]
+    }).collect();
+    let options = ModelOptions{increment: 4, limit: Some(1 << 12)};
+    let coded = encode_bytes(&text, &model, &options, &coder).unwrap();
+    return (text, coded);
+}
+
+fn container_error(coded: &[u8]) -> ArithmeticError {
+    return decode_bytes(coded).unwrap_err();
+}
+
+#[test]
+fn header_describes_the_code() {
+    let (text, coded) = coded_text(ModelType::ORDER2, CoderType::RANGE);
+    let header = Header::read(&mut &coded[..]).unwrap();
+    assert_eq!(header.model, ModelType::ORDER2);
+    assert_eq!(header.coder, CoderType::RANGE);
+    assert_eq!(header.options, ModelOptions{increment: 4, limit: Some(1 << 12)});
+    assert_eq!(header.text_len, text.len() as u64);
+    assert_eq!(header.checksum, crc32fast::hash(&text));
+}
+
+#[test]
+fn lengths_containing_newline_bytes() {
+    for len in [10, 266, 2570, 0x0A0A0A] {
+        let text: Vec<u8> = (0..len).map(|i| (i % 3) as u8).collect();
+        let coded = encode_bytes(&text, &ModelType::ORDER0, &ModelOptions::default(), &CoderType::BIT).unwrap();
+        assert_eq!(decode_bytes(&coded).unwrap(), text);
+    }
+}
+
+#[test]
+fn rejects_bad_magic() {
+    let (_, mut coded) = coded_text(ModelType::ORDER0, CoderType::BIT);
+    coded[0] ^= 0xFF;
+    assert!(matches!(container_error(&coded), ArithmeticError::BadHeader(HeaderError::BadMagic)));
+}
+
+#[test]
+fn rejects_unsupported_version() {
+    let (_, mut coded) = coded_text(ModelType::ORDER0, CoderType::BIT);
+    coded[4] = 0xEE;
+    assert!(matches!(container_error(&coded), ArithmeticError::BadHeader(HeaderError::UnsupportedVersion(0xEE))));
+}
+
+#[test]
+fn rejects_unknown_identifiers() {
+    let (_, mut coded) = coded_text(ModelType::ORDER0, CoderType::BIT);
+    coded[5] = 9;
+    assert!(matches!(container_error(&coded), ArithmeticError::BadHeader(HeaderError::UnknownModel(9))));
+
+    let (_, mut coded) = coded_text(ModelType::ORDER0, CoderType::BIT);
+    coded[6] = 9;
+    assert!(matches!(container_error(&coded), ArithmeticError::BadHeader(HeaderError::UnknownCoder(9))));
+}
+
+#[test]
+fn rejects_invalid_options() {
+    for options in [ModelOptions{increment: 0, limit: None}, ModelOptions{increment: 64, limit: Some(32)}] {
+        let header = Header{model: ModelType::ORDER1, options, coder: CoderType::BIT, text_len: 9, checksum: 0, streamed: false};
+        let mut coded = Vec::new();
+        header.write(&mut coded).unwrap();
+        assert!(matches!(container_error(&coded), ArithmeticError::BadHeader(HeaderError::InvalidOptions)));
+    }
+}
+
+#[test]
+fn rejects_truncated_header() {
+    let (_, coded) = coded_text(ModelType::ORDER1, CoderType::BIT);
+    for len in 0..10 {
+        assert!(matches!(container_error(&coded[..len]), ArithmeticError::TruncatedInput));
+    }
+}
+
+#[test]
+fn detects_corrupted_code() {
+    for coder in [CoderType::BIT, CoderType::RANGE] {
+        for model in [ModelType::ORDER0, ModelType::ORDER1, ModelType::ORDER2, ModelType::STATIC] {
+            let (_, coded) = coded_text(model, coder);
+
+            // truncated or damaged code must not decode silently
+
+            for cut in [1, 40] {
+                assert!(matches!(decode_bytes(&coded[..coded.len() - cut]), Err(ArithmeticError::TruncatedInput)));
+            }
+
+            let mut damaged = coded.clone();
+            let middle = damaged.len() / 2;
+            damaged[middle] ^= 0x55;
+            assert!(decode_bytes(&damaged).is_err());
+        }
+    }
+}
+
+#[test]
+fn rejects_overflowing_frequency_table() {
+    let header = Header {
+        model: ModelType::STATIC,
+        options: ModelOptions::default(),
+        coder: CoderType::BIT,
+        text_len: 1,
+        checksum: 0,
+        streamed: false,
+    };
+    let mut coded = Vec::new();
+    header.write(&mut coded).unwrap();
+
+    // a single byte occuring 2^40 times
+
+    let mut bitmap = [0u8; 32];
+    bitmap[0] = 1;
+    coded.extend_from_slice(&bitmap);
+    coded.extend_from_slice(&[0x80, 0x80, 0x80, 0x80, 0x80, 0x20]);
+    coded.extend_from_slice(&[0u8; 16]);
+
+    assert!(matches!(decode_bytes(&coded), Err(ArithmeticError::ModelOverflow)));
+}
+
+#[test]
+fn rejects_frequencies_overflowing_the_total() {
+    let header = Header {
+        model: ModelType::STATIC,
+        options: ModelOptions::default(),
+        coder: CoderType::BIT,
+        text_len: 1,
+        checksum: 0,
+        streamed: false,
+    };
+    let mut coded = Vec::new();
+    header.write(&mut coded).unwrap();
+
+    // every byte occuring 2^63 - 1 times
+
+    coded.extend_from_slice(&[0xFF; 32]);
+    for _ in 0..256 {
+        coded.extend_from_slice(&[0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x7F]);
+    }
+    coded.extend_from_slice(&[0u8; 16]);
+
+    assert!(matches!(decode_bytes(&coded), Err(ArithmeticError::ModelOverflow)));
+}
+
+#[test]
+fn rejects_varints_above_64_bits() {
+    let header = Header {
+        model: ModelType::ORDER0,
+        options: ModelOptions::default(),
+        coder: CoderType::BIT,
+        text_len: 1,
+        checksum: 0,
+        streamed: false,
+    };
+    let mut coded = Vec::new();
+    header.write(&mut coded).unwrap();
+
+    // a text length with its 10th byte carrying bits 64 and above
+
+    let len_pos = coded.len() - 5;
+    coded.truncate(len_pos);
+    coded.extend_from_slice(&[0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x03]);
+    coded.extend_from_slice(&[0u8; 4]);
+
+    assert!(matches!(decode_bytes(&coded), Err(ArithmeticError::BadHeader(HeaderError::InvalidVarint))));
+}
+
+#[test]
+fn damaged_files_never_panic() {
+    for coder in [CoderType::BIT, CoderType::RANGE] {
+        for model in [ModelType::ORDER0, ModelType::ORDER2, ModelType::STATIC] {
+            let (text, coded) = coded_text(model, coder);
+            for pos in (0..coded.len()).step_by(7) {
+                let mut damaged = coded.clone();
+                damaged[pos] = damaged[pos].wrapping_add(pos as u8 | 1);
+                if let Ok(decoded) = decode_bytes(&damaged) {
+                    assert_eq!(decoded, text);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn damaged_streams_never_panic() {
+    let (text, _) = coded_text(ModelType::ORDER0, CoderType::BIT);
+    for coder in [CoderType::BIT, CoderType::RANGE] {
+        let mut coded = Vec::new();
+        encode(&mut &text[..], &mut coded, &ModelType::ORDER1, &ModelOptions::default(), &coder).unwrap();
+        for pos in (0..coded.len()).step_by(7) {
+            let mut damaged = coded.clone();
+            damaged[pos] = damaged[pos].wrapping_add(pos as u8 | 1);
+            if let Ok(decoded) = decode_bytes(&damaged) {
+                assert_eq!(decoded, text);
+            }
+        }
+    }
+}
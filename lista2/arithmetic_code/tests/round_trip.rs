@@ -0,0 +1,143 @@
+use arithmetic_code::*;
+
+fn round_trip_with(text: &[u8], options: &ModelOptions) {
+    for coder in [CoderType::BIT, CoderType::RANGE] {
+        for model in [ModelType::ORDER0, ModelType::ORDER1, ModelType::ORDER2, ModelType::STATIC] {
+            let coded = encode_bytes(text, &model, options, &coder).unwrap();
+            let decoded = decode_bytes(&coded).unwrap();
+            assert_eq!(decoded, text);
+        }
+    }
+}
+
+fn round_trip(text: &[u8]) {
+    round_trip_with(text, &ModelOptions::default());
+}
+
+#[test]
+fn round_trip_short_text() {
+    round_trip(b"abababa");
+}
+
+#[test]
+fn round_trip_all_bytes() {
+    let text: Vec<u8> = (0..=255).cycle().take(1000).collect();
+    round_trip(&text);
+}
+
+#[test]
+fn round_trip_skewed_text() {
+    let text: Vec<u8> = (0..5000u32).map(|i| if i % 17 == 0 { b'b' } else { b'a' }).collect();
+    round_trip(&text);
+}
+
+#[test]
+fn round_trip_lengths_around_chunk_size() {
+    for len in [0, 1, 2, 255, 256, 257, 511, 512, 513] {
+        let text: Vec<u8> = (0..len).map(|i| (i * 7 % 13) as u8).collect();
+        round_trip(&text);
+    }
+}
+
+#[test]
+fn round_trip_pseudo_random_texts() {
+    let mut state: u64 = 0x2545F4914F6CDD1D;
+    for len in (0..2000).step_by(97) {
+        let text: Vec<u8> = (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                (state >> 59) as u8
+            })
+            .collect();
+        round_trip(&text);
+    }
+}
+
+#[test]
+fn round_trip_with_aging() {
+    let text: Vec<u8> = (0..20000u32).map(|i| if i < 10000 { (i % 3) as u8 } else { b'x' + (i % 5) as u8 }).collect();
+    round_trip_with(&text, &ModelOptions{increment: 32, limit: Some(1024)});
+    round_trip_with(&text, &ModelOptions{increment: 1, limit: Some(300)});
+}
+
+#[test]
+fn bit_and_range_coders_agree() {
+    let text: Vec<u8> = (0..3000u32).map(|i| b"arithmetic coding "[(i * i % 18) as usize]).collect();
+    let options = ModelOptions::default();
+
+    let bit_coded = encode_bytes(&text, &ModelType::ORDER1, &options, &CoderType::BIT).unwrap();
+    let range_coded = encode_bytes(&text, &ModelType::ORDER1, &options, &CoderType::RANGE).unwrap();
+    assert_ne!(bit_coded, range_coded);
+
+    // the code lengths of both coders should stay close to each other
+
+    let difference = (bit_coded.len() as f64 - range_coded.len() as f64).abs();
+    assert!(difference < 0.01 * bit_coded.len() as f64 + 16.0);
+
+    assert_eq!(decode_bytes(&bit_coded).unwrap(), text);
+    assert_eq!(decode_bytes(&range_coded).unwrap(), text);
+}
+
+#[test]
+fn stats_count_the_whole_output() {
+    let text: Vec<u8> = (0..777u32).map(|i| (i * i % 31) as u8).collect();
+    for coder in [CoderType::BIT, CoderType::RANGE] {
+        for model in [ModelType::ORDER0, ModelType::ORDER1, ModelType::ORDER2, ModelType::STATIC] {
+            let mut coded = Vec::new();
+            let stats = encode_seekable(&mut std::io::Cursor::new(&text), &mut coded, &model, &ModelOptions::default(), &coder).unwrap();
+            assert_eq!(stats.input_len, text.len() as u64);
+            assert_eq!(stats.output_len, coded.len() as u64);
+            assert_eq!(stats.bits_per_symbol, coded.len() as f64 * 8.0 / text.len() as f64);
+
+            if model != ModelType::STATIC {
+                let mut coded = Vec::new();
+                let stats = encode(&mut &text[..], &mut coded, &model, &ModelOptions::default(), &coder).unwrap();
+                assert_eq!(stats.input_len, text.len() as u64);
+                assert_eq!(stats.output_len, coded.len() as u64);
+            }
+        }
+    }
+}
+
+#[test]
+fn encode_from_a_stream() {
+
+    // a slice can be read but not rewound, like a pipe; the lengths around the chunks of the stream
+
+    for len in [0, 3000, 65535, 65536, 65537, 140000] {
+        let text: Vec<u8> = (0..len as u32).map(|i| (i * 7 % 13) as u8).collect();
+        for model in [ModelType::ORDER0, ModelType::ORDER1, ModelType::ORDER2] {
+            for coder in [CoderType::BIT, CoderType::RANGE] {
+                let mut coded = Vec::new();
+                let stats = encode(&mut &text[..], &mut coded, &model, &ModelOptions::default(), &coder).unwrap();
+                assert_eq!(stats.input_len, text.len() as u64);
+
+                let mut decoded = Vec::new();
+                let header = decode(&mut &coded[..], &mut decoded).unwrap();
+                assert!(header.streamed);
+                assert_eq!(header.text_len, text.len() as u64);
+                assert_eq!(header.checksum, crc32fast::hash(&text));
+                assert_eq!(decoded, text);
+            }
+        }
+    }
+}
+
+#[test]
+fn static_model_needs_a_seekable_input() {
+    let text = b"abracadabra";
+    assert!(matches!(encode(&mut &text[..], &mut Vec::new(), &ModelType::STATIC, &ModelOptions::default(), &CoderType::BIT), Err(ArithmeticError::UnseekableInput)));
+}
+
+#[test]
+fn rejects_invalid_options() {
+    let invalid = [ModelOptions{increment: 0, limit: None}, ModelOptions{increment: 0, limit: Some(100)}, ModelOptions{increment: 64, limit: Some(32)}];
+    for options in invalid {
+        for model in [ModelType::ORDER0, ModelType::ORDER1, ModelType::ORDER2, ModelType::STATIC] {
+            for coder in [CoderType::BIT, CoderType::RANGE] {
+                assert!(matches!(encode_bytes(b"abcabcabc", &model, &options, &coder), Err(ArithmeticError::InvalidOptions)));
+                assert!(matches!(encode(&mut &b"abcabcabc"[..], &mut Vec::new(), &model, &options, &coder), Err(ArithmeticError::InvalidOptions)));
+            }
+        }
+    }
+}
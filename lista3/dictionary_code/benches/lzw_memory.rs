@@ -0,0 +1,137 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+use dictionary_code::lzw;
+
+/* peak heap usage of the LZW encoder and decoder, compared with the former trie
+ * of 256-pointer arrays and decoder cloning every entry; run with `cargo bench` */
+
+struct CountingAllocator;
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let current = CURRENT.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+        PEAK.fetch_max(current, Ordering::Relaxed);
+        return System.alloc(layout);
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout);
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+// the peak of the memory allocated by {f} on top of what was allocated before
+
+fn measure<T, F: FnOnce() -> T>(f: F) -> (T, usize, f64) {
+    let before = CURRENT.load(Ordering::Relaxed);
+    PEAK.store(before, Ordering::Relaxed);
+    let start = Instant::now();
+    let result = f();
+    let elapsed = start.elapsed().as_secs_f64();
+    return (result, PEAK.load(Ordering::Relaxed) - before, elapsed);
+}
+
+const NO_BYTES: usize = 256;
+
+struct BoxedTrie {
+    value: usize,
+    next_entries: [Option<Box<BoxedTrie>>; NO_BYTES],
+}
+
+impl BoxedTrie {
+    fn new(value: usize) -> Self {
+        return Self {
+            value,
+            next_entries: std::array::from_fn(|_| None),
+        };
+    }
+}
+
+fn boxed_trie_encode(message: &[u8]) -> Vec<usize> {
+    let mut coded = Vec::new();
+    let mut root = BoxedTrie::new(0);
+    for byte in 0..NO_BYTES {
+        root.next_entries[byte] = Some(Box::new(BoxedTrie::new(byte)));
+    }
+
+    let mut curr_prefix = &mut root;
+    let mut next_value = NO_BYTES;
+    for &byte in message {
+        if curr_prefix.next_entries[byte as usize].is_none() {
+            coded.push(curr_prefix.value);
+            curr_prefix.next_entries[byte as usize] = Some(Box::new(BoxedTrie::new(next_value)));
+            next_value += 1;
+            curr_prefix = &mut root;
+        }
+        curr_prefix = curr_prefix.next_entries[byte as usize].as_mut().unwrap();
+    }
+    coded.push(curr_prefix.value);
+    return coded;
+}
+
+fn cloning_decode(coded: &[usize]) -> Vec<u8> {
+    let mut decoded = Vec::new();
+    let mut dict_bytes: Vec<Vec<u8>> = (0..NO_BYTES).map(|byte| vec![byte as u8]).collect();
+    for &index in coded {
+        if dict_bytes.len() > NO_BYTES {
+            let last_symbol = dict_bytes[index][0];
+            dict_bytes.last_mut().unwrap().push(last_symbol);
+        }
+        decoded.extend(&dict_bytes[index]);
+        dict_bytes.push(dict_bytes[index].clone());
+    }
+    return decoded;
+}
+
+// text-like input: pseudo-random words of a small vocabulary
+
+fn sample_text(len: usize) -> Vec<u8> {
+    let words: Vec<String> = (0..2000u32)
+        .map(|i| (0..(3 + i % 7)).map(|j| (b'a' + ((i * 31 + j * 17) % 26) as u8) as char).collect())
+        .collect();
+    let mut seed: u32 = 1;
+    let mut text = Vec::with_capacity(len + 16);
+    while text.len() < len {
+        seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+        text.extend(words[(seed >> 12) as usize % words.len()].as_bytes());
+        text.push(b' ');
+    }
+    text.truncate(len);
+    return text;
+}
+
+fn mib(bytes: usize) -> f64 {
+    return bytes as f64 / (1 << 20) as f64;
+}
+
+fn main() {
+    println!("{:>10} {:>24} {:>12} {:>10}", "input MiB", "implementation", "peak MiB", "seconds");
+
+    for len in [1 << 18, 1 << 20, 1 << 22, 1 << 24] {
+        let text = sample_text(len);
+
+        let (coded, encode_peak, encode_time) = measure(|| lzw::encode(&text));
+        let (decoded, decode_peak, decode_time) = measure(|| lzw::decode(&coded));
+        assert_eq!(decoded, text);
+        println!("{:>10.2} {:>24} {:>12.2} {:>10.3}", mib(len), "map trie encode", mib(encode_peak), encode_time);
+        println!("{:>10.2} {:>24} {:>12.2} {:>10.3}", mib(len), "prefix pointer decode", mib(decode_peak), decode_time);
+
+        // the former representation needs gigabytes beyond a few MiB
+
+        if len <= 1 << 20 {
+            let (old_coded, encode_peak, encode_time) = measure(|| boxed_trie_encode(&text));
+            assert_eq!(old_coded, coded);
+            let (decoded, decode_peak, decode_time) = measure(|| cloning_decode(&coded));
+            assert_eq!(decoded, text);
+            println!("{:>10.2} {:>24} {:>12.2} {:>10.3}", mib(len), "boxed array trie encode", mib(encode_peak), encode_time);
+            println!("{:>10.2} {:>24} {:>12.2} {:>10.3}", mib(len), "cloning decode", mib(decode_peak), decode_time);
+        }
+    }
+}
@@ -0,0 +1,197 @@
+use std::io;
+use std::io::Read;
+use std::io::Write;
+
+/* packed bit I/O shared by the codes: bits go most significant first, collected in a 64-bit word,
+ * so a code takes a bit of memory per bit and can be written to or read from any stream */
+
+const BUFFER_SIZE: usize = 4096;
+const WORD_BITS: u32 = 64;
+
+pub struct BitWriter<W: Write> {
+    out: W,
+    word: u64,
+    word_len: u32,
+    bits_written: u64,
+}
+
+impl<W: Write> BitWriter<W> {
+    pub fn new(out: W) -> Self {
+        return Self {
+            out,
+            word: 0,
+            word_len: 0,
+            bits_written: 0,
+        };
+    }
+
+    pub fn write_bit(&mut self, bit: bool) -> io::Result<()> {
+        return self.write_bits(bit as u64, 1);
+    }
+
+    // the {width} lowest bits of the value, the most significant first
+
+    pub fn write_bits(&mut self, value: u64, width: u32) -> io::Result<()> {
+        if width == 0 {
+            return Ok(());
+        }
+        let value = value & (u64::MAX >> (WORD_BITS - width));
+        self.bits_written += width as u64;
+
+        let free = WORD_BITS - self.word_len;
+        if width < free {
+            self.word = (self.word << width) | value;
+            self.word_len += width;
+            return Ok(());
+        }
+
+        // the word fills up, the rest of the value starts the next one
+
+        let rest = width - free;
+        let head = if free == WORD_BITS { value } else { (self.word << free) | (value >> rest) };
+        self.out.write_all(&head.to_be_bytes())?;
+        self.word = if rest == 0 { 0 } else { value & (u64::MAX >> (WORD_BITS - rest)) };
+        self.word_len = rest;
+        return Ok(());
+    }
+
+    pub fn write_zeros(&mut self, count: u32) -> io::Result<()> {
+        for _ in 0..(count / WORD_BITS) {
+            self.write_bits(0, WORD_BITS)?;
+        }
+        return self.write_bits(0, count % WORD_BITS);
+    }
+
+    pub fn bits_written(&self) -> u64 {
+        return self.bits_written;
+    }
+
+    // pad the last byte with {pad_bit} and flush everything to the output
+
+    pub fn finish(mut self, pad_bit: bool) -> io::Result<W> {
+        let no_bytes = self.word_len.div_ceil(8);
+        if no_bytes > 0 {
+            let padding = no_bytes * 8 - self.word_len;
+            let pad = if pad_bit { (1 << padding) - 1 } else { 0 };
+            let padded = ((self.word << padding) | pad) << (WORD_BITS - no_bytes * 8);
+            self.out.write_all(&padded.to_be_bytes()[..(no_bytes as usize)])?;
+        }
+        self.out.flush()?;
+        return Ok(self.out);
+    }
+}
+
+// reads bits most significant first; None is returned once the input has fewer bits than asked for
+
+pub struct BitReader<R: Read> {
+    input: R,
+    buffer: Vec<u8>,
+    buffer_pos: usize,
+    buffer_len: usize,
+
+    // the bits not read yet, aligned to the most significant end
+
+    word: u64,
+    word_len: u32,
+}
+
+impl<R: Read> BitReader<R> {
+    pub fn new(input: R) -> Self {
+        return Self {
+            input,
+            buffer: vec![0; BUFFER_SIZE],
+            buffer_pos: 0,
+            buffer_len: 0,
+            word: 0,
+            word_len: 0,
+        };
+    }
+
+    // load whole bytes into the word, leaving it with more than 56 bits unless the input ends
+
+    fn refill(&mut self) -> io::Result<()> {
+        while self.word_len <= WORD_BITS - 8 {
+            if self.buffer_pos == self.buffer_len {
+                self.buffer_len = loop {
+                    match self.input.read(&mut self.buffer) {
+                        Ok(n) => break n,
+                        Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                        Err(e) => return Err(e),
+                    }
+                };
+                self.buffer_pos = 0;
+
+                if self.buffer_len == 0 {
+                    return Ok(());
+                }
+            }
+
+            self.word |= (self.buffer[self.buffer_pos] as u64) << (WORD_BITS - 8 - self.word_len);
+            self.word_len += 8;
+            self.buffer_pos += 1;
+        }
+        return Ok(());
+    }
+
+    pub fn read_bit(&mut self) -> io::Result<Option<bool>> {
+        return Ok(self.read_bits(1)?.map(|bit| bit == 1));
+    }
+
+    // {width} bits as a number, at most 64 of them
+
+    pub fn read_bits(&mut self, width: u32) -> io::Result<Option<u64>> {
+        if width == 0 {
+            return Ok(Some(0));
+        }
+
+        // a refilled word holds at least 32 bits unless the input ends
+
+        if width > WORD_BITS / 2 {
+            let low_width = WORD_BITS / 2;
+            let high = match self.read_bits(width - low_width)? {
+                Some(high) => high,
+                None => return Ok(None),
+            };
+            return Ok(self.read_bits(low_width)?.map(|low| (high << low_width) | low));
+        }
+
+        if width > self.word_len {
+            self.refill()?;
+            if width > self.word_len {
+                return Ok(None);
+            }
+        }
+
+        let value = self.word >> (WORD_BITS - width);
+        self.word <<= width;
+        self.word_len -= width;
+        return Ok(Some(value));
+    }
+}
+
+// writing to memory can't fail, so the codes of whole slices go through these
+
+pub(crate) fn encode_all<F>(values: &[usize], pad_bit: bool, mut encode_one: F) -> Vec<u8>
+where
+    F: FnMut(usize, &mut BitWriter<Vec<u8>>) -> io::Result<()>,
+{
+    let mut writer = BitWriter::new(Vec::new());
+    for &value in values {
+        encode_one(value, &mut writer).expect("writing to memory can't fail");
+    }
+    return writer.finish(pad_bit).expect("writing to memory can't fail");
+}
+
+// decoding stops at the first value cut short by the end of the code
+
+pub(crate) fn decode_all<'a, F>(coded: &'a [u8], mut decode_one: F) -> Vec<usize>
+where
+    F: FnMut(&mut BitReader<&'a [u8]>) -> io::Result<Option<usize>>,
+{
+    let mut reader = BitReader::new(coded);
+    let mut values = Vec::new();
+    while let Ok(Some(value)) = decode_one(&mut reader) {
+        values.push(value);
+    }
+    return values;
+}
@@ -0,0 +1,169 @@
+use std::io::Read;
+use crate::CodeType;
+use crate::error::{DictionaryError, HeaderError};
+use crate::options::{DictionaryAlgorithm, DictionaryOptions, DictionaryPolicy};
+
+/* header of the compressed file:
+ *   magic bytes "LZWC", format version,
+ *   code and dictionary algorithm,
+ *   for LZSS the window and lookahead sizes as varints, within the bounds of the lzss module,
+ *   for the other algorithms the policy and maximum dictionary size plus one (0 meaning no maximum) as a varint,
+ *   message length as a varint, CRC32 of the message (little-endian) */
+
+pub const MAGIC: [u8; 4] = *b"LZWC";
+pub const VERSION: u8 = 1;
+
+#[derive(Debug)]
+#[derive(PartialEq)]
+pub struct Header {
+    pub code: CodeType,
+    pub dictionary: DictionaryOptions,
+    pub message_len: u64,
+    pub checksum: u32,
+}
+
+fn code_id(code: &CodeType) -> u8 {
+    return match code {
+        CodeType::GAMMA => 0,
+        CodeType::DELTA => 1,
+        CodeType::OMEGA => 2,
+        CodeType::FIB => 3,
+        CodeType::VARIABLE_WIDTH => 4,
+    };
+}
+
+fn code_from_id(id: u8) -> Result<CodeType, HeaderError> {
+    return match id {
+        0 => Ok(CodeType::GAMMA),
+        1 => Ok(CodeType::DELTA),
+        2 => Ok(CodeType::OMEGA),
+        3 => Ok(CodeType::FIB),
+        4 => Ok(CodeType::VARIABLE_WIDTH),
+        _ => Err(HeaderError::UnknownCode(id)),
+    };
+}
+
+fn algorithm_id(algorithm: &DictionaryAlgorithm) -> u8 {
+    return match algorithm {
+        DictionaryAlgorithm::LZW => 0,
+        DictionaryAlgorithm::LZ78 => 1,
+        DictionaryAlgorithm::LZMW => 2,
+        DictionaryAlgorithm::LZAP => 3,
+        DictionaryAlgorithm::LZSS => 4,
+    };
+}
+
+fn algorithm_from_id(id: u8) -> Result<DictionaryAlgorithm, HeaderError> {
+    return match id {
+        0 => Ok(DictionaryAlgorithm::LZW),
+        1 => Ok(DictionaryAlgorithm::LZ78),
+        2 => Ok(DictionaryAlgorithm::LZMW),
+        3 => Ok(DictionaryAlgorithm::LZAP),
+        4 => Ok(DictionaryAlgorithm::LZSS),
+        _ => Err(HeaderError::UnknownAlgorithm(id)),
+    };
+}
+
+fn policy_id(policy: &DictionaryPolicy) -> u8 {
+    return match policy {
+        DictionaryPolicy::FREEZE => 0,
+        DictionaryPolicy::RESET => 1,
+        DictionaryPolicy::LRU => 2,
+    };
+}
+
+fn policy_from_id(id: u8) -> Result<DictionaryPolicy, HeaderError> {
+    return match id {
+        0 => Ok(DictionaryPolicy::FREEZE),
+        1 => Ok(DictionaryPolicy::RESET),
+        2 => Ok(DictionaryPolicy::LRU),
+        _ => Err(HeaderError::UnknownPolicy(id)),
+    };
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8 & 0x7F) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+// a varint of more than 64 bits is invalid, its high bits aren't dropped
+
+fn read_varint<R: Read>(input: &mut R) -> Result<u64, DictionaryError> {
+    let mut value = 0;
+    for shift in (0..64).step_by(7) {
+        let mut byte = [0u8; 1];
+        input.read_exact(&mut byte)?;
+        if shift == 63 && byte[0] & 0x7F > 1 {
+            return Err(HeaderError::InvalidVarint.into());
+        }
+        value |= ((byte[0] & 0x7F) as u64) << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    return Err(HeaderError::InvalidVarint.into());
+}
+
+impl Header {
+    // the compressed files are built in memory, so writing can't fail
+
+    pub fn write(&self, out: &mut Vec<u8>) {
+        out.extend(MAGIC);
+        out.extend([VERSION, code_id(&self.code), algorithm_id(&self.dictionary.algorithm)]);
+        if self.dictionary.algorithm == DictionaryAlgorithm::LZSS {
+            write_varint(out, self.dictionary.window.window_size() as u64);
+            write_varint(out, self.dictionary.window.lookahead_size() as u64);
+        } else {
+            out.push(policy_id(&self.dictionary.policy));
+            write_varint(out, self.dictionary.max_size.map_or(0, |max_size| (max_size as u64).saturating_add(1)));
+        }
+        write_varint(out, self.message_len);
+        out.extend(self.checksum.to_le_bytes());
+    }
+
+    pub fn read<R: Read>(input: &mut R) -> Result<Self, DictionaryError> {
+        let mut magic = [0u8; 4];
+        input.read_exact(&mut magic)?;
+        if magic != MAGIC {
+            return Err(HeaderError::BadMagic.into());
+        }
+
+        let mut ids = [0u8; 3];
+        input.read_exact(&mut ids)?;
+        if ids[0] != VERSION {
+            return Err(HeaderError::UnsupportedVersion(ids[0]).into());
+        }
+        let code = code_from_id(ids[1])?;
+        let algorithm = algorithm_from_id(ids[2])?;
+
+        // the options absent from the header keep their defaults; the length of the hash chains
+        // matters only for compressing
+
+        let mut dictionary = DictionaryOptions{algorithm, ..Default::default()};
+        if algorithm == DictionaryAlgorithm::LZSS {
+            dictionary.window.window_size = usize::try_from(read_varint(input)?).unwrap_or(usize::MAX);
+            dictionary.window.lookahead_size = usize::try_from(read_varint(input)?).unwrap_or(usize::MAX);
+            if !dictionary.window.is_valid() {
+                return Err(HeaderError::InvalidWindow.into());
+            }
+        } else {
+            let mut policy = [0u8; 1];
+            input.read_exact(&mut policy)?;
+            dictionary.policy = policy_from_id(policy[0])?;
+            dictionary.max_size = read_varint(input)?.checked_sub(1).map(|max_size| max_size as usize);
+        }
+        let message_len = read_varint(input)?;
+        let mut checksum = [0u8; 4];
+        input.read_exact(&mut checksum)?;
+
+        return Ok(Self {
+            code,
+            dictionary,
+            message_len,
+            checksum: u32::from_le_bytes(checksum),
+        });
+    }
+}
@@ -1,184 +1,131 @@
-type BIT = u8;
+use std::io;
+use std::io::Read;
+use std::io::Write;
+use crate::bits::{decode_all, encode_all, BitReader, BitWriter};
 
-const BYTE_SIZE: u8 = 8;
+// the codes work on {value} + 1, which needs 65 bits for the largest values; the bit lengths
+// are counted exactly from the leading zeros, since floating point rounds up just below the powers of two
 
-fn convert_to_bin_rev(n: usize) -> Vec<BIT> {
-    let no_bits = f64::log2(n as f64).floor() as usize;
-    return (0..=no_bits)
-        .map(|bit| ((n >> bit) & 1) as BIT)
-        .collect();
+fn bit_length(n: u128) -> u32 {
+    return u128::BITS - n.leading_zeros();
 }
 
-fn convert_to_bin(n: usize) -> Vec<BIT> {
-    let no_bits = f64::log2(n as f64).floor() as usize;
-    return (0..=no_bits)
-        .rev()
-        .map(|bit| ((n >> bit) & 1) as BIT)
-        .collect();
+fn write_number<W: Write>(n: u128, len: u32, writer: &mut BitWriter<W>) -> io::Result<()> {
+    if len > u64::BITS {
+        writer.write_bits((n >> u64::BITS) as u64, len - u64::BITS)?;
+    }
+    return writer.write_bits(n as u64, len.min(u64::BITS));
 }
 
-fn convert_to_bin_no_leading(n: usize) -> Vec<u8> {
-    let no_bits = f64::log2(n as f64).floor() as usize;
-    return (0..no_bits)
-        .rev()
-        .map(|bit| ((n >> bit) & 1) as BIT)
-        .collect();
-}
+// the value of {n} = {value} + 1, None if it's beyond the values
 
-fn bytes_to_bits(v: &[u8]) -> Vec<BIT> {
-    let b = v
-        .iter()
-        .fold(Vec::new(), |mut bits, byte| {
-            for pos in (0..BYTE_SIZE).rev() {
-                bits.push((byte >> pos) & 1);
-            }
-            return bits;
-        });
-    return b;
+fn to_value(n: u128) -> Option<usize> {
+    return usize::try_from(n - 1).ok();
 }
 
 
 
-pub fn gamma_encode_one(value: usize) -> Vec<BIT> {
-    //let no_bits = 
-    let mut bin_rep = convert_to_bin(value + 1);
-    let mut coded = vec![0; bin_rep.len() - 1];
-    coded.append(&mut bin_rep);
-    return coded;
+pub fn gamma_encode_one<W: Write>(value: usize, writer: &mut BitWriter<W>) -> io::Result<()> {
+    let n = value as u128 + 1;
+    let len = bit_length(n);
+    writer.write_zeros(len - 1)?;
+    return write_number(n, len, writer);
 }
 
-pub fn gamma_encode(values: &[usize]) -> Vec<BIT> {
-    return values.iter().fold(Vec::new(), |mut coded, &val| {
-        coded.append(&mut gamma_encode_one(val));
-        return coded;
-    });
+pub fn gamma_encode(values: &[usize]) -> Vec<u8> {
+    return encode_all(values, false, gamma_encode_one);
 }
 
-pub fn gamma_decode_one(coded: &[BIT]) -> Option<(usize, &[BIT])> {
-    let one_idx = coded
-        .iter()
-        .position(|&bit| bit == 1);
-    if let Some(value_idx) = one_idx {
-        //println!("{:?}", Into::<Vec<_>>::into(&coded[value_idx..=(2 * value_idx)]));
-        let value = coded[value_idx..=(2 * value_idx)]
-            .iter()
-            .fold(0, |acc, &bit| (acc << 1) + bit as usize);
-        //println!("{}", value);
-        return Some((value - 1, &coded[(2 * value_idx + 1)..]));
-    } else {
-        return None;
+// None at the end of the code, or when the code is longer than any number
+
+pub fn gamma_decode_one<R: Read>(reader: &mut BitReader<R>) -> io::Result<Option<usize>> {
+    let mut no_zeros = 0;
+    loop {
+        match reader.read_bit()? {
+            Some(true) => break,
+            Some(false) => no_zeros += 1,
+            None => return Ok(None),
+        }
+        if no_zeros > usize::BITS {
+            return Ok(None);
+        }
     }
+    return Ok(reader.read_bits(no_zeros)?.and_then(|rest| to_value((1 << no_zeros) | rest as u128)));
 }
 
 pub fn gamma_decode(coded: &[u8]) -> Vec<usize> {
-    let mut coded_left = &bytes_to_bits(&coded)[..];
-    let mut values = Vec::new();
-    while coded_left.len() > 0 {
-        if let Some((decoded_value, left)) = gamma_decode_one(&coded_left) {
-            values.push(decoded_value);
-            coded_left = left;
-        } else {
-            break;
-        }
-    }
-    return values;
+    return decode_all(coded, gamma_decode_one);
 }
 
 
 
-pub fn delta_encode_one(value: usize) -> Vec<BIT> {
-    let mut bin_rep = convert_to_bin_no_leading(value + 1);
-    let mut coded = gamma_encode_one(bin_rep.len());
-    coded.append(&mut bin_rep);
-    return coded;
+pub fn delta_encode_one<W: Write>(value: usize, writer: &mut BitWriter<W>) -> io::Result<()> {
+    let n = value as u128 + 1;
+    let len = bit_length(n) - 1;
+    gamma_encode_one(len as usize, writer)?;
+    return write_number(n, len, writer);
 }
 
-pub fn delta_encode(values: &[usize]) -> Vec<BIT> {
-    return values.iter().fold(Vec::new(), |mut coded, &val| {
-        coded.append(&mut delta_encode_one(val));
-        return coded;
-    });
+pub fn delta_encode(values: &[usize]) -> Vec<u8> {
+    return encode_all(values, false, delta_encode_one);
 }
 
-pub fn delta_decode_one(coded: &[BIT]) -> Option<(usize, &[BIT])> {
-    let code_split = gamma_decode_one(coded);
-    if let Some((bin_rep_len, coded_left)) = code_split {
-        let value = (1 << bin_rep_len) + coded_left[..bin_rep_len]
-            .iter()
-            .fold(0, |acc, &bit| (acc << 1) + bit as usize);
-        return Some((value - 1, &coded_left[bin_rep_len..]));
-    } else {
-        return None;
-    }
+pub fn delta_decode_one<R: Read>(reader: &mut BitReader<R>) -> io::Result<Option<usize>> {
+    let len = match gamma_decode_one(reader)? {
+        Some(len) if len <= usize::BITS as usize => len as u32,
+        _ => return Ok(None),
+    };
+    return Ok(reader.read_bits(len)?.and_then(|rest| to_value((1 << len) | rest as u128)));
 }
 
 pub fn delta_decode(coded: &[u8]) -> Vec<usize> {
-    let mut coded_left = &bytes_to_bits(&coded)[..];
-    let mut values = Vec::new();
-    while coded_left.len() > 0 {
-        if let Some((decoded_value, left)) = delta_decode_one(&coded_left) {
-            values.push(decoded_value);
-            coded_left = left;
-        } else {
-            break;
-        }
-    }
-    return values;
+    return decode_all(coded, delta_decode_one);
 }
 
 
 
-pub fn omega_encode_one(mut value: usize) -> Vec<BIT> {
-    value += 1;
-    let mut coded = vec![0];
-    while value > 1 {
-        let mut bin_rep = convert_to_bin_rev(value);
-        //println!("{:?}", bin_rep);
-        value = bin_rep.len() - 1;
-        coded.append(&mut bin_rep);
+// the groups are found from the number down to the length of its length..., and written the other way round
+
+pub fn omega_encode_one<W: Write>(value: usize, writer: &mut BitWriter<W>) -> io::Result<()> {
+    let mut n = value as u128 + 1;
+    let mut groups = Vec::new();
+    while n > 1 {
+        let len = bit_length(n);
+        groups.push((n, len));
+        n = len as u128 - 1;
+    }
+
+    for &(group, len) in groups.iter().rev() {
+        write_number(group, len, writer)?;
     }
-    return coded
-        .iter()
-        .rev()
-        .map(|&b| b)
-        .collect();
+    return writer.write_bit(false);
 }
 
-pub fn omega_encode(values: &[usize]) -> Vec<BIT> {
-    return values.iter().fold(Vec::new(), |mut coded, &val| {
-        coded.append(&mut omega_encode_one(val));
-        return coded;
-    })
+pub fn omega_encode(values: &[usize]) -> Vec<u8> {
+
+    // padded with ones, since a zero alone is a whole codeword
+
+    return encode_all(values, true, omega_encode_one);
 }
 
-pub fn omega_decode_one(coded: &[BIT]) -> Option<(usize, &[BIT])> {
-    let mut coded_left = coded;
-    let mut value = 1;
-    while coded_left[0] != 0 {
-        //println!("{:?}", <&[char] as Into<Vec<char>>>::into(&coded[..=value]));
-        if coded_left.len() > value + 1 {
-            let cutoff = value + 1;
-            value = coded_left[..cutoff]
-                .iter()
-                .fold(0, |acc, &bit| (acc << 1) + bit as usize);
-            coded_left = &coded_left[cutoff..];
-        } else {
-            return None;
+pub fn omega_decode_one<R: Read>(reader: &mut BitReader<R>) -> io::Result<Option<usize>> {
+    let mut n: u128 = 1;
+    loop {
+        match reader.read_bit()? {
+            Some(false) => return Ok(to_value(n)),
+            Some(true) if n <= usize::BITS as u128 => {},
+            _ => return Ok(None),
         }
+
+        // the group is n + 1 bits long, its leading one already read
+
+        n = match reader.read_bits(n as u32)? {
+            Some(rest) => (1 << n) | rest as u128,
+            None => return Ok(None),
+        };
     }
-    return Some((value - 1, &coded_left[1..]));
 }
 
 pub fn omega_decode(coded: &[u8]) -> Vec<usize> {
-    let mut coded_left = &bytes_to_bits(&coded)[..];
-    let mut values = Vec::new();
-    while coded_left.len() > 0 {
-        if let Some((decoded_value, left)) = omega_decode_one(&coded_left) {
-            values.push(decoded_value);
-            coded_left = left;
-        } else {
-            break;
-        }
-    }
-    return values;
+    return decode_all(coded, omega_decode_one);
 }
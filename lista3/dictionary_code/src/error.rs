@@ -0,0 +1,92 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+// what is wrong with the header of the compressed file
+
+#[derive(Debug)]
+#[derive(PartialEq)]
+pub enum HeaderError {
+    BadMagic,
+    UnsupportedVersion(u8),
+    UnknownCode(u8),
+    UnknownAlgorithm(u8),
+    UnknownPolicy(u8),
+
+    // the window or lookahead size is out of the bounds of LZSS
+
+    InvalidWindow,
+    InvalidVarint,
+}
+
+impl fmt::Display for HeaderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            HeaderError::BadMagic => write!(f, "not a dictionary compressed file"),
+            HeaderError::UnsupportedVersion(version) => write!(f, "unsupported format version {}", version),
+            HeaderError::UnknownCode(id) => write!(f, "unknown code identifier {}", id),
+            HeaderError::UnknownAlgorithm(id) => write!(f, "unknown dictionary algorithm identifier {}", id),
+            HeaderError::UnknownPolicy(id) => write!(f, "unknown dictionary policy identifier {}", id),
+            HeaderError::InvalidWindow => write!(f, "invalid window or lookahead size"),
+            HeaderError::InvalidVarint => write!(f, "invalid varint"),
+        };
+    }
+}
+
+/* errors of decompression; corrupted input is reported here and never panics */
+
+#[derive(Debug)]
+pub enum DictionaryError {
+
+    // the input ended before the header was complete
+
+    TruncatedInput,
+    BadHeader(HeaderError),
+
+    // the code refers outside of the decoded message or the window
+
+    CorruptedCode,
+
+    // the decoded message is shorter or longer than the one compressed
+
+    LengthMismatch{expected: u64, actual: u64},
+    ChecksumMismatch{expected: u32, actual: u32},
+    Io(io::Error),
+}
+
+impl fmt::Display for DictionaryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            DictionaryError::TruncatedInput => write!(f, "the compressed input is truncated"),
+            DictionaryError::BadHeader(e) => write!(f, "bad header of the compressed file: {}", e),
+            DictionaryError::CorruptedCode => write!(f, "failed to decode the message: the code is corrupted"),
+            DictionaryError::LengthMismatch{expected, actual} => write!(f, "length mismatch: expected {} bytes, got {}", expected, actual),
+            DictionaryError::ChecksumMismatch{expected, actual} => write!(f, "checksum mismatch: expected {:08x}, got {:08x}", expected, actual),
+            DictionaryError::Io(e) => write!(f, "{}", e),
+        };
+    }
+}
+
+impl Error for DictionaryError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        return match self {
+            DictionaryError::Io(e) => Some(e),
+            _ => None,
+        };
+    }
+}
+
+impl From<io::Error> for DictionaryError {
+    fn from(e: io::Error) -> Self {
+        return match e.kind() {
+            io::ErrorKind::UnexpectedEof => DictionaryError::TruncatedInput,
+            _ => DictionaryError::Io(e),
+        };
+    }
+}
+
+impl From<HeaderError> for DictionaryError {
+    fn from(e: HeaderError) -> Self {
+        return DictionaryError::BadHeader(e);
+    }
+}
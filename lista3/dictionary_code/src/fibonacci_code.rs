@@ -1,129 +1,85 @@
-type BIT = u8;
+use std::io;
+use std::io::Read;
+use std::io::Write;
+use crate::bits::{decode_all, encode_all, BitReader, BitWriter};
 
-const BYTE_SIZE: u8 = 8;
+// extend the table of Fibonacci numbers 1, 2, 3, 5, ... up to the first one above {value};
+// false if it's beyond the numbers
 
-fn bytes_to_bits(v: &[u8]) -> Vec<BIT> {
-    let b = v
-        .iter()
-        .fold(Vec::new(), |mut bits, byte| {
-            for pos in (0..BYTE_SIZE).rev() {
-                bits.push((byte >> pos) & 1);
-            }
-            return bits;
-        });
-    return b;
+fn extend_fib_table(fib_table: &mut Vec<usize>, value: usize) -> bool {
+    while fib_table[fib_table.len() - 1] <= value {
+        match fib_table[fib_table.len() - 1].checked_add(fib_table[fib_table.len() - 2]) {
+            Some(next_fib) => fib_table.push(next_fib),
+            None => return false,
+        }
+    }
+    return true;
 }
 
+// the Zeckendorf representation from the smallest Fibonacci number up, followed by a closing one
 
+pub fn fib_encode_one<W: Write>(value: usize, fib_table: &mut Vec<usize>, writer: &mut BitWriter<W>) -> io::Result<()> {
 
-pub fn fib_encode_one(mut value: usize, fib_table: &mut Vec<usize>) -> Vec<BIT> {
-    //let mut coded = vec![1];
+    // {value} + 1 may be 2^64, above all the Fibonacci numbers in the table but still below the next one
 
-    value += 1;
-    let max_fib = fib_table
+    let mut n = value as u128 + 1;
+    extend_fib_table(fib_table, value);
+    let curr_fib_idx = fib_table
         .iter()
-        .position(|&fib_value| fib_value > value);
+        .rposition(|&fib_value| fib_value as u128 <= n)
+        .unwrap();
 
-    let curr_fib_idx = if max_fib.is_some() {
-        max_fib.unwrap() - 1
-    } else {
-        let mut next_fib = fib_table[fib_table.len() - 1] + fib_table[fib_table.len() - 2];
-        while next_fib <= value {
-            fib_table.push(next_fib);
-            next_fib = fib_table[fib_table.len() - 1] + fib_table[fib_table.len() - 2];
-        }
-        fib_table.push(next_fib);
-        fib_table.len() - 2
-    };
-    
-    return fib_table[..=curr_fib_idx]
-        .iter()
-        .rev()
-        .fold(vec![1], |mut coded, &fib_value| {
-            if fib_value <= value {
-                value -= fib_value;
-                coded.push(1);
-            } else {
-                coded.push(0);
-            }
-            return coded;
-        })
-        .iter()
-        .rev()
-        .map(|&v| v)
-        .collect();
-    /*
-    loop {
-        coded.push(1);
-        value -= fib_table[curr_fib_idx];
+    // there are fewer than 128 Fibonacci numbers below 2^64, so the used ones fit in a mask
 
-        if value == 0 {
-            if curr_fib_idx > 0 {
-                for _ in 0..curr_fib_idx {
-                    coded.push(0);
-                }
-            }
-            break;
-        } else {
-            curr_fib_idx -= 1;
-            let fib_idx_decrease = fib_table[..curr_fib_idx]
-                .iter()
-                .rev()
-                .position(|&fib_value| fib_value <= value)
-                .unwrap();
-            for _ in 1..fib_idx_decrease {
-                coded.push(0);
-            }
-            curr_fib_idx -= fib_idx_decrease;
+    let mut used: u128 = 0;
+    for idx in (0..=curr_fib_idx).rev() {
+        if fib_table[idx] as u128 <= n {
+            n -= fib_table[idx] as u128;
+            used |= 1 << idx;
         }
     }
-    return coded;
-    */
+
+    for idx in 0..=curr_fib_idx {
+        writer.write_bit((used >> idx) & 1 == 1)?;
+    }
+    return writer.write_bit(true);
 }
 
-pub fn fib_encode(values: &[usize]) -> Vec<BIT> {
+pub fn fib_encode(values: &[usize]) -> Vec<u8> {
     let mut fib_table = vec![1, 2];
-    return values.iter().fold(Vec::new(), |mut coded, &val| {
-        coded.append(&mut fib_encode_one(val, &mut fib_table));
-        return coded;
-    });
+    return encode_all(values, false, |value, writer| fib_encode_one(value, &mut fib_table, writer));
 }
 
-pub fn fib_decode_one<'a, 'b>(coded: &'a[BIT], fib_table: &'b mut Vec<usize>) -> Option<(usize, &'a[BIT])> {
-    let double_ones = (1..coded.len())
-        .position(|idx| coded[idx - 1] == 1 && coded[idx] == 1);
-    if let Some(code_end) = double_ones {
-        if fib_table.len() <= code_end {
-            for _ in 0..(code_end + 1 - fib_table.len()) {
-                let next_fib = fib_table[fib_table.len() - 1] + fib_table[fib_table.len() - 2];
-                fib_table.push(next_fib);
-            }
+// None at the end of the code, or when the code is longer than any number
+
+pub fn fib_decode_one<R: Read>(reader: &mut BitReader<R>, fib_table: &mut Vec<usize>) -> io::Result<Option<usize>> {
+    let mut n: u128 = 0;
+    let mut prev_bit = false;
+    let mut idx = 0;
+
+    loop {
+        let bit = match reader.read_bit()? {
+            Some(bit) => bit,
+            None => return Ok(None),
+        };
+        if bit && prev_bit {
+            return Ok(usize::try_from(n - 1).ok());
         }
-        let value = (0..=code_end)
-            .fold(0, |acc, idx| {
-                if coded[idx] == 1 {
-                    acc + fib_table[idx]
-                } else {
-                    acc
+
+        if bit {
+            while idx >= fib_table.len() {
+                if !extend_fib_table(fib_table, fib_table[fib_table.len() - 1]) {
+                    return Ok(None);
                 }
-            });
-        return Some((value - 1, &coded[(code_end + 2)..]));
-    } else {
-        return None;
+            }
+            n += fib_table[idx] as u128;
+        }
+        prev_bit = bit;
+        idx += 1;
     }
 }
 
 pub fn fib_decode(coded: &[u8]) -> Vec<usize> {
     let mut fib_table = vec![1, 2];
-    let mut coded_left = &bytes_to_bits(&coded)[..];
-    let mut values = Vec::new();
-    while coded_left.len() > 0 {
-        if let Some((decoded_value, left)) = fib_decode_one(&coded_left, &mut fib_table) {
-            values.push(decoded_value);
-            coded_left = left;
-        } else {
-            break;
-        }
-    }
-    return values;
+    return decode_all(coded, |reader| fib_decode_one(reader, &mut fib_table));
 }
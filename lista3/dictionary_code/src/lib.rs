@@ -1,81 +1,165 @@
+use std::time::Instant;
+use entropy::accumulator::EntropyAccumulator;
+use entropy::stats::CompressionStats;
+
+pub mod bits;
 pub mod lzw;
+pub mod lz78;
+pub mod lzmw;
+pub mod lzss;
 pub mod elias_code;
 pub mod fibonacci_code;
+pub mod variable_width;
+pub mod container;
+pub mod error;
+pub mod options;
 
-type BIT = u8;
+pub use crate::container::Header;
+pub use crate::error::{DictionaryError, HeaderError};
+pub use crate::options::{DictionaryAlgorithm, DictionaryOptions, DictionaryPolicy};
 
+#[allow(non_camel_case_types)]
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(Debug)]
+#[derive(PartialEq)]
 pub enum CodeType {
     GAMMA,
     DELTA,
     OMEGA,
     FIB,
+    VARIABLE_WIDTH,
 }
 
-fn pad_zeros(v: &mut Vec<BIT>) {
-    if v.len() % 8 != 0 {
-        for _ in 0..(8 - v.len() % 8) {
-            v.push(0);
-        }
-    }
+pub fn compress_str(message: String, code: &CodeType, dictionary: &DictionaryOptions) -> Vec<u8> {
+    return compress_bytes(message.as_bytes(), code, dictionary);
 }
 
-fn pad_ones(v: &mut Vec<BIT>) {
-    if v.len() % 8 != 0 {
-        for _ in 0..(8 - v.len() % 8) {
-            v.push(1);
-        }
-    }
-}
+// code the numbers with the universal code, or with {fixed_width} for the binary layout of the algorithm
 
-fn bits_to_bytes(bits: &[BIT]) -> Vec<u8> {
-    return bits
-        .chunks(8)
-        .fold(Vec::<u8>::new(), |mut bytes, chunk| {
-            bytes.push(
-                chunk
-                .iter()
-                .fold(0, |acc, &bit| (acc << 1) + bit as u8)
-            );
-            bytes
-        });
+fn encode_values<F: FnOnce(&[usize]) -> Vec<u8>>(values: &[usize], code: &CodeType, fixed_width: F) -> Vec<u8> {
+    return match code {
+        CodeType::GAMMA => elias_code::gamma_encode(values),
+        CodeType::DELTA => elias_code::delta_encode(values),
+        CodeType::OMEGA => elias_code::omega_encode(values),
+        CodeType::FIB => fibonacci_code::fib_encode(values),
+        CodeType::VARIABLE_WIDTH => fixed_width(values),
+    };
 }
 
-pub fn compress_str(message: String, code: &CodeType) -> Vec<u8> {
-    return compress_bytes(message.as_bytes(), code);
+fn decode_values<F: FnOnce(&[u8]) -> Vec<usize>>(coded: &[u8], code: &CodeType, fixed_width: F) -> Vec<usize> {
+    return match code {
+        CodeType::GAMMA => elias_code::gamma_decode(coded),
+        CodeType::DELTA => elias_code::delta_decode(coded),
+        CodeType::OMEGA => elias_code::omega_decode(coded),
+        CodeType::FIB => fibonacci_code::fib_decode(coded),
+        CodeType::VARIABLE_WIDTH => fixed_width(coded),
+    };
 }
 
-pub fn compress_bytes(message_bytes: &[u8], code: &CodeType) -> Vec<u8> {
-    let lzw_code = lzw::encode(message_bytes);
-    return match code {
-        CodeType::GAMMA => {
-            let mut code_bits = elias_code::gamma_encode(&lzw_code);
-            pad_zeros(&mut code_bits);
-            bits_to_bytes(&code_bits)
+// the dictionary algorithm is chosen by the options; with VARIABLE_WIDTH only LZW has the layout of Unix compress,
+// and LZSS the classic layout of flags and fixed-width fields;
+// without the header of compress_bytes the code and the options have to be known to decompress
+
+pub fn compress_raw_bytes(message_bytes: &[u8], code: &CodeType, dictionary: &DictionaryOptions) -> Vec<u8> {
+    return match dictionary.algorithm {
+        DictionaryAlgorithm::LZW => {
+            let lzw_code = lzw::encode_with(message_bytes, dictionary);
+            encode_values(&lzw_code, code, |values| variable_width::encode(values, dictionary))
         },
-        CodeType::DELTA => {
-            let mut code_bits = elias_code::delta_encode(&lzw_code);
-            pad_zeros(&mut code_bits);
-            bits_to_bytes(&code_bits)
+        DictionaryAlgorithm::LZ78 => {
+            let lz78_code = lz78::encode_with(message_bytes, dictionary);
+            encode_values(&lz78_code, code, |values| lz78::pack(values, dictionary))
         },
-        CodeType::OMEGA => {
-            let mut code_bits = elias_code::omega_encode(&lzw_code);
-            pad_ones(&mut code_bits);
-            bits_to_bytes(&code_bits)
+        DictionaryAlgorithm::LZMW | DictionaryAlgorithm::LZAP => {
+            let lzmw_code = lzmw::encode_with(message_bytes, dictionary);
+            encode_values(&lzmw_code, code, |values| lzmw::pack(values, dictionary))
         },
-        CodeType::FIB => {
-            let mut code_bits = fibonacci_code::fib_encode(&lzw_code);
-            pad_zeros(&mut code_bits);
-            bits_to_bytes(&code_bits)
+        DictionaryAlgorithm::LZSS => {
+            let lzss_code = lzss::tokens_to_values(&lzss::encode(message_bytes, &dictionary.window));
+            encode_values(&lzss_code, code, |values| lzss::pack(values, &dictionary.window))
         },
     };
 }
 
-pub fn decompress_bytes(coded: &[u8], code: &CodeType) -> Vec<u8> {
-    let lzw_code = match code {
-        CodeType::GAMMA => elias_code::gamma_decode(&coded),
-        CodeType::DELTA => elias_code::delta_decode(&coded),
-        CodeType::OMEGA => elias_code::omega_decode(&coded),
-        CodeType::FIB => fibonacci_code::fib_decode(&coded),
+pub fn compress_bytes(message_bytes: &[u8], code: &CodeType, dictionary: &DictionaryOptions) -> Vec<u8> {
+    let header = Header {
+        code: *code,
+        dictionary: *dictionary,
+        message_len: message_bytes.len() as u64,
+        checksum: crc32fast::hash(message_bytes),
+    };
+    let mut compressed = Vec::new();
+    header.write(&mut compressed);
+    compressed.extend(compress_raw_bytes(message_bytes, code, dictionary));
+    return compressed;
+}
+
+pub fn compress_bytes_with_stats(message_bytes: &[u8], code: &CodeType, dictionary: &DictionaryOptions) -> (Vec<u8>, CompressionStats) {
+    let start_time = Instant::now();
+    let compressed = compress_bytes(message_bytes, code, dictionary);
+    let elapsed = start_time.elapsed();
+
+    // the statistics of the message come from the shared accumulator of the entropy crate
+
+    let mut statistics = EntropyAccumulator::new();
+    statistics.update(message_bytes);
+    let stats = CompressionStats::new(
+        statistics.total_length() as u64,
+        compressed.len() as u64,
+        statistics.entropy(),
+        elapsed,
+    );
+    return (compressed, stats);
+}
+
+// the code and the options come from the header; the decoded message is checked against its length and checksum
+
+pub fn decompress_bytes(compressed: &[u8]) -> Result<Vec<u8>, DictionaryError> {
+    let mut input = compressed;
+    let header = Header::read(&mut input)?;
+
+    let max_len = usize::try_from(header.message_len).unwrap_or(usize::MAX);
+    let decoded = decompress_up_to(input, &header.code, &header.dictionary, max_len)?;
+    if decoded.len() as u64 != header.message_len {
+        return Err(DictionaryError::LengthMismatch{expected: header.message_len, actual: decoded.len() as u64});
+    }
+    let checksum = crc32fast::hash(&decoded);
+    if checksum != header.checksum {
+        return Err(DictionaryError::ChecksumMismatch{expected: header.checksum, actual: checksum});
+    }
+
+    return Ok(decoded);
+}
+
+// the dictionary algorithms stop at the first code absent from the dictionary,
+// while LZSS rejects a code which refers outside of its window
+
+pub fn decompress_raw_bytes(coded: &[u8], code: &CodeType, dictionary: &DictionaryOptions) -> Result<Vec<u8>, DictionaryError> {
+    return decompress_up_to(coded, code, dictionary, usize::MAX);
+}
+
+// all the algorithms also stop once the message is longer than {max_len}, i.e. the length from the header,
+// which a corrupted code would otherwise make grow without bound
+
+fn decompress_up_to(coded: &[u8], code: &CodeType, dictionary: &DictionaryOptions, max_len: usize) -> Result<Vec<u8>, DictionaryError> {
+    let decoded = match dictionary.algorithm {
+        DictionaryAlgorithm::LZW => {
+            let lzw_code = decode_values(coded, code, |coded| variable_width::decode(coded, dictionary));
+            lzw::decode_up_to(&lzw_code, dictionary, max_len)
+        },
+        DictionaryAlgorithm::LZ78 => {
+            let lz78_code = decode_values(coded, code, |coded| lz78::unpack(coded, dictionary));
+            lz78::decode_up_to(&lz78_code, dictionary, max_len)
+        },
+        DictionaryAlgorithm::LZMW | DictionaryAlgorithm::LZAP => {
+            let lzmw_code = decode_values(coded, code, |coded| lzmw::unpack(coded, dictionary));
+            lzmw::decode_up_to(&lzmw_code, dictionary, max_len)
+        },
+        DictionaryAlgorithm::LZSS => {
+            let lzss_code = decode_values(coded, code, |coded| lzss::unpack(coded, &dictionary.window));
+            lzss::decode_up_to(&lzss::values_to_tokens(&lzss_code)?, &dictionary.window, max_len)?
+        },
     };
-    return lzw::decode(&lzw_code);
+    return Ok(decoded);
 }
@@ -0,0 +1,365 @@
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+use crate::options::{DictionaryOptions, DictionaryPolicy};
+use crate::variable_width::{self, Widths, MIN_WIDTH};
+
+/* LZ78: the message as pairs of the longest phrase of the dictionary and the byte following it,
+ * which together become the next phrase; the last phrase may come without its byte */
+
+type Byte = u8;
+
+const NO_BYTES: usize = 256;
+const BYTE_WIDTH: u32 = 8;
+
+/* the dictionaries of LZ78 and LZMW/LZAP: every phrase is a substring of the message, kept as its position and length,
+ * and found through a trie whose nodes include the prefixes which aren't phrases themselves;
+ * the first codes are the empty phrase (for LZ78) or the single bytes (for LZMW/LZAP) */
+
+pub(crate) struct Phrases {
+    options: DictionaryOptions,
+    single_bytes: bool,
+    first_code: usize,
+    entries: Vec<(usize, usize)>,
+    children: HashMap<usize, usize>,
+    node_codes: Vec<Option<usize>>,
+
+    // the key of every node in {children} and its number of children, so that the nodes left
+    // without a phrase below them can be removed and reused
+
+    node_keys: Vec<usize>,
+    node_children: Vec<usize>,
+    free_nodes: Vec<usize>,
+
+    // for the LRU policy the trie node of every entry and when it was last used; the entries don't refer
+    // to each other, so any of them can be evicted, and its node is pruned along with the ancestors
+    // which become mere prefixes of nothing
+
+    entry_nodes: Vec<usize>,
+    last_used: Vec<u64>,
+    by_last_use: BTreeSet<(u64, usize)>,
+    clock: u64,
+}
+
+impl Phrases {
+    pub(crate) fn new(options: &DictionaryOptions, single_bytes: bool) -> Self {
+        let mut phrases = Self {
+            options: *options,
+            single_bytes,
+            first_code: if single_bytes { NO_BYTES } else { 1 },
+            entries: Vec::new(),
+            children: HashMap::new(),
+            node_codes: Vec::new(),
+            node_keys: Vec::new(),
+            node_children: Vec::new(),
+            free_nodes: Vec::new(),
+            entry_nodes: Vec::new(),
+            last_used: Vec::new(),
+            by_last_use: BTreeSet::new(),
+            clock: 0,
+        };
+        phrases.reset();
+        return phrases;
+    }
+
+    fn key(node: usize, byte: Byte) -> usize {
+        return (node << 8) | byte as usize;
+    }
+
+    fn reset(&mut self) {
+        self.entries.clear();
+        self.children.clear();
+        self.node_codes.clear();
+        self.node_keys.clear();
+        self.node_children.clear();
+        self.free_nodes.clear();
+        self.entry_nodes.clear();
+        self.last_used.clear();
+        self.by_last_use.clear();
+        if self.single_bytes {
+            self.push_node(0, None);
+            for byte in 0..NO_BYTES {
+                self.children.insert(Self::key(0, byte as Byte), byte + 1);
+                self.push_node(Self::key(0, byte as Byte), Some(byte));
+            }
+            self.node_children[0] = NO_BYTES;
+        } else {
+            self.push_node(0, Some(0));
+        }
+    }
+
+    fn push_node(&mut self, key: usize, code: Option<usize>) {
+        self.node_codes.push(code);
+        self.node_keys.push(key);
+        self.node_children.push(0);
+    }
+
+    // the child of the node for the byte, added unless it's there; a removed node is reused first
+
+    fn child(&mut self, node: usize, byte: Byte) -> usize {
+        let key = Self::key(node, byte);
+        if let Some(&child) = self.children.get(&key) {
+            return child;
+        }
+
+        let child = match self.free_nodes.pop() {
+            Some(child) => {
+                self.node_keys[child] = key;
+                child
+            },
+            None => {
+                self.push_node(key, None);
+                self.node_codes.len() - 1
+            },
+        };
+        self.children.insert(key, child);
+        self.node_children[node] += 1;
+        return child;
+    }
+
+    // remove the node unless it has a phrase or children, then its parent likewise, up to the root;
+    // {keep} is the node about to get a phrase
+
+    fn prune(&mut self, mut node: usize, keep: usize) {
+        while node != 0 && node != keep && self.node_codes[node].is_none() && self.node_children[node] == 0 {
+            let key = self.node_keys[node];
+            self.children.remove(&key);
+            self.free_nodes.push(node);
+            node = key >> 8;
+            self.node_children[node] -= 1;
+        }
+    }
+
+    pub(crate) fn size(&self) -> usize {
+        return self.first_code + self.entries.len();
+    }
+
+    fn is_full(&self) -> bool {
+        return self.options.max_size.is_some_and(|max_size| self.size() >= max_size);
+    }
+
+    fn is_lru(&self) -> bool {
+        return self.options.policy == DictionaryPolicy::LRU;
+    }
+
+    // mark the phrase as used; the encoder and the decoder do it for the same codes in the same order
+
+    pub(crate) fn touch(&mut self, code: usize) {
+        if !self.is_lru() || code < self.first_code {
+            return;
+        }
+        let idx = code - self.first_code;
+        self.clock += 1;
+        self.by_last_use.remove(&(self.last_used[idx], code));
+        self.by_last_use.insert((self.clock, code));
+        self.last_used[idx] = self.clock;
+    }
+
+    // the code for a new phrase at {node}: the next one, the one of the least recently used phrase under LRU,
+    // or none when the dictionary is full
+
+    fn new_code(&mut self, node: usize) -> Option<usize> {
+        if !self.is_full() {
+            self.entries.push((0, 0));
+            self.entry_nodes.push(0);
+            self.last_used.push(0);
+            return Some(self.size() - 1);
+        }
+        if !self.is_lru() {
+            return None;
+        }
+
+        let (_, code) = self.by_last_use.pop_first()?;
+        let evicted = self.entry_nodes[code - self.first_code];
+        self.node_codes[evicted] = None;
+        self.prune(evicted, node);
+        return Some(code);
+    }
+
+    // the code of the longest phrase starting the bytes, and its length
+
+    pub(crate) fn longest_match(&self, bytes: &[Byte]) -> (usize, usize) {
+        let mut node = 0;
+        let mut best = (0, 0);
+        for (len, &byte) in bytes.iter().enumerate() {
+            match self.children.get(&Self::key(node, byte)) {
+                Some(&child) => node = child,
+                None => break,
+            }
+            if let Some(code) = self.node_codes[node] {
+                best = (code, len + 1);
+            }
+        }
+        return best;
+    }
+
+    // the prefixes of the text at {start} of the given lengths, in a single walk down the trie;
+    // a full dictionary is cleared under the RESET policy, replaces its least recently used phrases under LRU
+    // and is left as it is under FREEZE, and phrases already in the dictionary aren't added again
+
+    pub(crate) fn add(&mut self, text: &[Byte], start: usize, lens: RangeInclusive<usize>) {
+        let mut node = 0;
+        let mut depth = 0;
+
+        for len in lens {
+            if self.is_full() && self.options.policy == DictionaryPolicy::RESET && !self.entries.is_empty() {
+                self.reset();
+                node = 0;
+                depth = 0;
+            }
+            if self.is_full() && !self.is_lru() {
+                return;
+            }
+
+            while depth < len {
+                node = self.child(node, text[start + depth]);
+                depth += 1;
+            }
+            if self.node_codes[node].is_none() {
+                let code = match self.new_code(node) {
+                    Some(code) => code,
+                    None => {
+                        self.prune(node, 0);
+                        return;
+                    },
+                };
+                let idx = code - self.first_code;
+                self.node_codes[node] = Some(code);
+                self.entries[idx] = (start, len);
+                self.entry_nodes[idx] = node;
+                self.touch(code);
+            }
+        }
+    }
+
+    // append the bytes of the phrase, which are earlier in the same message
+
+    pub(crate) fn write_entry(&self, code: usize, out: &mut Vec<Byte>) {
+        if code < self.first_code {
+            if self.single_bytes {
+                out.push(code as Byte);
+            }
+            return;
+        }
+        let (start, len) = self.entries[code - self.first_code];
+        out.extend_from_within(start..(start + len));
+    }
+
+    // the width of the binary code of any phrase
+
+    pub(crate) fn code_width(&self) -> u32 {
+        return (usize::BITS - (self.size() - 1).leading_zeros()).max(MIN_WIDTH);
+    }
+}
+
+pub fn encode_with(message: &[Byte], options: &DictionaryOptions) -> Vec<usize> {
+    let mut coded = Vec::new();
+    let mut phrases = Phrases::new(options, false);
+    let mut pos = 0;
+
+    while pos < message.len() {
+        let (code, len) = phrases.longest_match(&message[pos..]);
+        phrases.touch(code);
+        coded.push(code);
+        if pos + len == message.len() {
+            break;
+        }
+
+        coded.push(message[pos + len] as usize);
+        phrases.add(message, pos, (len + 1)..=(len + 1));
+        pos += len + 1;
+    }
+
+    return coded;
+}
+
+/* the decoder takes a value at a time, so that it also gives the widths of the binary codes:
+ * the code of a phrase is as wide as the largest code in the dictionary, the byte takes 8 bits */
+
+struct Decoder {
+    phrases: Phrases,
+    decoded: Vec<Byte>,
+    pending: Option<usize>,
+    valid: bool,
+}
+
+impl Decoder {
+    fn new(options: &DictionaryOptions) -> Self {
+        return Self {
+            phrases: Phrases::new(options, false),
+            decoded: Vec::new(),
+            pending: None,
+            valid: true,
+        };
+    }
+
+    // false from the first value which can't be decoded on
+
+    fn push(&mut self, value: usize) -> bool {
+        if !self.valid {
+            return false;
+        }
+
+        match self.pending.take() {
+            None if value < self.phrases.size() => self.pending = Some(value),
+            Some(code) if value < NO_BYTES => {
+                let start = self.decoded.len();
+                self.phrases.touch(code);
+                self.phrases.write_entry(code, &mut self.decoded);
+                self.decoded.push(value as Byte);
+                let len = self.decoded.len() - start;
+                self.phrases.add(&self.decoded, start, len..=len);
+            },
+            _ => self.valid = false,
+        }
+        return self.valid;
+    }
+
+    fn finish(mut self) -> Vec<Byte> {
+        if let Some(code) = self.pending.filter(|_| self.valid) {
+            self.phrases.write_entry(code, &mut self.decoded);
+        }
+        return self.decoded;
+    }
+}
+
+impl Widths for Decoder {
+    fn next_width(&mut self) -> (u32, u32) {
+        return match self.pending {
+            Some(_) => (0, BYTE_WIDTH),
+            None => (0, self.phrases.code_width()),
+        };
+    }
+
+    fn consume(&mut self, value: usize) {
+        self.push(value);
+    }
+}
+
+// decoding stops at the first code absent from the dictionary
+
+pub fn decode_with(coded: &[usize], options: &DictionaryOptions) -> Vec<Byte> {
+    return decode_up_to(coded, options, usize::MAX);
+}
+
+// decoding also stops once the message is longer than {max_len}
+
+pub fn decode_up_to(coded: &[usize], options: &DictionaryOptions, max_len: usize) -> Vec<Byte> {
+    let mut decoder = Decoder::new(options);
+    for &value in coded {
+        if decoder.decoded.len() > max_len || !decoder.push(value) {
+            break;
+        }
+    }
+    return decoder.finish();
+}
+
+// binary codes packed from the least significant bit, without the groups of the LZW layout
+
+pub fn pack(values: &[usize], options: &DictionaryOptions) -> Vec<u8> {
+    return variable_width::encode_with_widths(values, &mut Decoder::new(options));
+}
+
+pub fn unpack(coded: &[u8], options: &DictionaryOptions) -> Vec<usize> {
+    return variable_width::decode_with_widths(coded, &mut Decoder::new(options));
+}
@@ -0,0 +1,121 @@
+use std::ops::RangeInclusive;
+use crate::lz78::Phrases;
+use crate::options::{DictionaryAlgorithm, DictionaryOptions};
+use crate::variable_width::{self, Widths};
+
+/* LZMW and LZAP: the message as codes of the longest phrases, as in LZW, but after every phrase
+ * LZMW adds the previous phrase followed by the whole current one, and LZAP the previous phrase
+ * followed by every prefix of the current one; LZAP is used when the options say so, LZMW otherwise */
+
+type Byte = u8;
+
+// the lengths of the new phrases: the previous phrase followed by prefixes of the current one
+
+fn extensions(options: &DictionaryOptions, prev_len: usize, len: usize) -> RangeInclusive<usize> {
+    return match options.algorithm {
+        DictionaryAlgorithm::LZAP => (prev_len + 1)..=(prev_len + len),
+        _ => (prev_len + len)..=(prev_len + len),
+    };
+}
+
+pub fn encode_with(message: &[Byte], options: &DictionaryOptions) -> Vec<usize> {
+    let mut coded = Vec::new();
+    let mut phrases = Phrases::new(options, true);
+
+    // the previous phrase, which directly precedes the current one in the message
+
+    let mut prev: Option<(usize, usize)> = None;
+    let mut pos = 0;
+
+    while pos < message.len() {
+        let (code, len) = phrases.longest_match(&message[pos..]);
+        phrases.touch(code);
+        coded.push(code);
+        if let Some((prev_start, prev_len)) = prev {
+            phrases.add(message, prev_start, extensions(options, prev_len, len));
+        }
+        prev = Some((pos, len));
+        pos += len;
+    }
+
+    return coded;
+}
+
+// the decoder takes a code at a time, so that it also gives the width of the next binary code
+
+struct Decoder {
+    options: DictionaryOptions,
+    phrases: Phrases,
+    decoded: Vec<Byte>,
+    prev: Option<(usize, usize)>,
+    valid: bool,
+}
+
+impl Decoder {
+    fn new(options: &DictionaryOptions) -> Self {
+        return Self {
+            options: *options,
+            phrases: Phrases::new(options, true),
+            decoded: Vec::new(),
+            prev: None,
+            valid: true,
+        };
+    }
+
+    // false from the first code absent from the dictionary
+
+    fn push(&mut self, code: usize) -> bool {
+        if !self.valid || code >= self.phrases.size() {
+            self.valid = false;
+            return false;
+        }
+
+        let start = self.decoded.len();
+        self.phrases.touch(code);
+        self.phrases.write_entry(code, &mut self.decoded);
+        let len = self.decoded.len() - start;
+        if let Some((prev_start, prev_len)) = self.prev {
+            self.phrases.add(&self.decoded, prev_start, extensions(&self.options, prev_len, len));
+        }
+        self.prev = Some((start, len));
+        return true;
+    }
+}
+
+impl Widths for Decoder {
+    fn next_width(&mut self) -> (u32, u32) {
+        return (0, self.phrases.code_width());
+    }
+
+    fn consume(&mut self, value: usize) {
+        self.push(value);
+    }
+}
+
+// decoding stops at the first code absent from the dictionary
+
+pub fn decode_with(coded: &[usize], options: &DictionaryOptions) -> Vec<Byte> {
+    return decode_up_to(coded, options, usize::MAX);
+}
+
+// decoding also stops once the message is longer than {max_len}
+
+pub fn decode_up_to(coded: &[usize], options: &DictionaryOptions, max_len: usize) -> Vec<Byte> {
+    let mut decoder = Decoder::new(options);
+    for &code in coded {
+        if decoder.decoded.len() > max_len || !decoder.push(code) {
+            break;
+        }
+    }
+    return decoder.decoded;
+}
+
+// binary codes as wide as the largest code in the dictionary, packed from the least significant bit
+
+pub fn pack(values: &[usize], options: &DictionaryOptions) -> Vec<u8> {
+    return variable_width::encode_with_widths(values, &mut Decoder::new(options));
+}
+
+pub fn unpack(coded: &[u8], options: &DictionaryOptions) -> Vec<usize> {
+    return variable_width::decode_with_widths(coded, &mut Decoder::new(options));
+}
@@ -0,0 +1,282 @@
+use std::io;
+use std::io::Read;
+use std::io::Write;
+use crate::bits::{BitReader, BitWriter};
+use crate::error::DictionaryError;
+
+/* LZSS: the message as literals and (offset, length) references to the last {window_size} bytes,
+ * with the matches of at most {lookahead_size} bytes found through hash chains */
+
+type Byte = u8;
+
+pub const MIN_MATCH: usize = 3;
+
+// the sizes are kept within these bounds, which the header of a compressed file has to respect
+
+pub const MAX_WINDOW_SIZE: usize = 1 << 24;
+pub const MAX_LOOKAHEAD_SIZE: usize = 1 << 16;
+
+const HASH_BITS: u32 = 15;
+const NONE: usize = usize::MAX;
+
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(Debug)]
+#[derive(PartialEq)]
+pub struct WindowOptions {
+    pub window_size: usize,
+    pub lookahead_size: usize,
+
+    // how many earlier positions with the same hash are tried before settling for the longest match so far
+
+    pub max_chain: usize,
+}
+
+impl Default for WindowOptions {
+    fn default() -> Self {
+        return Self {
+            window_size: 1 << 15,
+            lookahead_size: 258,
+            max_chain: 128,
+        };
+    }
+}
+
+impl WindowOptions {
+    pub(crate) fn window_size(&self) -> usize {
+        return self.window_size.clamp(1, MAX_WINDOW_SIZE);
+    }
+
+    pub(crate) fn lookahead_size(&self) -> usize {
+        return self.lookahead_size.clamp(MIN_MATCH, MAX_LOOKAHEAD_SIZE);
+    }
+
+    pub(crate) fn is_valid(&self) -> bool {
+        return self.window_size == self.window_size() && self.lookahead_size == self.lookahead_size();
+    }
+}
+
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(Debug)]
+#[derive(PartialEq)]
+pub enum Token {
+    Literal(Byte),
+    Match{offset: usize, length: usize},
+}
+
+fn hash(bytes: &[Byte]) -> usize {
+    let value = (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32;
+    return (value.wrapping_mul(2654435761) >> (32 - HASH_BITS)) as usize;
+}
+
+// the positions of the window chained by the hashes of their first MIN_MATCH bytes
+
+struct HashChains {
+    head: Vec<usize>,
+    prev: Vec<usize>,
+}
+
+impl HashChains {
+    fn new(window_size: usize) -> Self {
+        return Self {
+            head: vec![NONE; 1 << HASH_BITS],
+            prev: vec![NONE; window_size],
+        };
+    }
+
+    fn insert(&mut self, message: &[Byte], pos: usize) {
+        if pos + MIN_MATCH > message.len() {
+            return;
+        }
+        let hash = hash(&message[pos..]);
+        let window_size = self.prev.len();
+        self.prev[pos % window_size] = self.head[hash];
+        self.head[hash] = pos;
+    }
+
+    fn longest_match(&self, message: &[Byte], pos: usize, options: &WindowOptions) -> Option<(usize, usize)> {
+        if pos + MIN_MATCH > message.len() {
+            return None;
+        }
+
+        let window_size = self.prev.len();
+        let max_length = options.lookahead_size().min(message.len() - pos);
+        let mut best: Option<(usize, usize)> = None;
+        let mut candidate = self.head[hash(&message[pos..])];
+
+        for _ in 0..options.max_chain {
+
+            // the chains may point to positions overwritten since, which are further than the window
+
+            if candidate == NONE || candidate >= pos || pos - candidate > window_size {
+                break;
+            }
+
+            let length = message[candidate..]
+                .iter()
+                .zip(&message[pos..(pos + max_length)])
+                .take_while(|(a, b)| a == b)
+                .count();
+            if length >= MIN_MATCH && best.is_none_or(|(_, best_length)| length > best_length) {
+                best = Some((pos - candidate, length));
+                if length == max_length {
+                    break;
+                }
+            }
+
+            candidate = self.prev[candidate % window_size];
+        }
+
+        return best;
+    }
+}
+
+pub fn encode(message: &[Byte], options: &WindowOptions) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chains = HashChains::new(options.window_size());
+    let mut pos = 0;
+
+    while pos < message.len() {
+        match chains.longest_match(message, pos, options) {
+            Some((offset, length)) => {
+                tokens.push(Token::Match{offset, length});
+                for matched in pos..(pos + length) {
+                    chains.insert(message, matched);
+                }
+                pos += length;
+            },
+            None => {
+                tokens.push(Token::Literal(message[pos]));
+                chains.insert(message, pos);
+                pos += 1;
+            },
+        }
+    }
+
+    return tokens;
+}
+
+// a corrupted code is rejected as soon as a match refers outside of the window or exceeds the lookahead,
+// so that a damaged length can't make the message grow without bound
+
+pub fn decode(tokens: &[Token], options: &WindowOptions) -> Result<Vec<Byte>, DictionaryError> {
+    return decode_up_to(tokens, options, usize::MAX);
+}
+
+// decoding also stops once the message is longer than {max_len}
+
+pub fn decode_up_to(tokens: &[Token], options: &WindowOptions, max_len: usize) -> Result<Vec<Byte>, DictionaryError> {
+    let mut decoded: Vec<Byte> = Vec::new();
+
+    for token in tokens {
+        if decoded.len() > max_len {
+            break;
+        }
+
+        match *token {
+            Token::Literal(byte) => decoded.push(byte),
+            Token::Match{offset, length} => {
+                if offset == 0 || offset > options.window_size() || offset > decoded.len() || length > options.lookahead_size() {
+                    return Err(DictionaryError::CorruptedCode);
+                }
+
+                // the match may overlap the bytes it produces
+
+                let start = decoded.len() - offset;
+                for idx in start..(start + length) {
+                    decoded.push(decoded[idx]);
+                }
+            },
+        }
+    }
+
+    return Ok(decoded);
+}
+
+/* the tokens as a single stream of numbers for the universal codes:
+ * a literal is 0 followed by the byte, a match is its length less MIN_MATCH - 1 followed by its offset less 1 */
+
+pub fn tokens_to_values(tokens: &[Token]) -> Vec<usize> {
+    return tokens.iter().fold(Vec::new(), |mut values, token| {
+        match *token {
+            Token::Literal(byte) => values.extend([0, byte as usize]),
+            Token::Match{offset, length} => values.extend([length - MIN_MATCH + 1, offset - 1]),
+        }
+        return values;
+    });
+}
+
+// the values of a corrupted code may be beyond any byte, offset or length
+
+pub fn values_to_tokens(values: &[usize]) -> Result<Vec<Token>, DictionaryError> {
+    return values
+        .chunks_exact(2)
+        .map(|pair| {
+            let token = match pair[0] {
+                0 => Byte::try_from(pair[1]).ok().map(Token::Literal),
+                length => pair[1].checked_add(1)
+                    .zip(length.checked_add(MIN_MATCH - 1))
+                    .map(|(offset, length)| Token::Match{offset, length}),
+            };
+            return token.ok_or(DictionaryError::CorruptedCode);
+        })
+        .collect();
+}
+
+/* the classic fixed-width layout, most significant bit first: a literal is a 0 bit followed by the byte,
+ * a match is a 1 bit followed by the offset and the length wide enough for the window and the lookahead */
+
+fn field_widths(options: &WindowOptions) -> (u32, u32) {
+    let width = |largest: usize| usize::BITS - largest.leading_zeros();
+    return (width(options.window_size() - 1), width(options.lookahead_size() - MIN_MATCH));
+}
+
+fn write_tokens<W: Write>(values: &[usize], options: &WindowOptions, writer: &mut BitWriter<W>) -> io::Result<()> {
+    let (offset_width, length_width) = field_widths(options);
+    for pair in values.chunks_exact(2) {
+        match pair[0] {
+            0 => {
+                writer.write_bit(false)?;
+                writer.write_bits(pair[1] as u64, 8)?;
+            },
+            length => {
+                writer.write_bit(true)?;
+                writer.write_bits(pair[1] as u64, offset_width)?;
+                writer.write_bits(length as u64 - 1, length_width)?;
+            },
+        }
+    }
+    return Ok(());
+}
+
+pub fn pack(values: &[usize], options: &WindowOptions) -> Vec<Byte> {
+    let mut writer = BitWriter::new(Vec::new());
+    write_tokens(values, options, &mut writer).expect("writing to memory can't fail");
+    return writer.finish(false).expect("writing to memory can't fail");
+}
+
+// the padding is shorter than a literal, so the unpacking stops at a token cut short
+
+fn read_token<R: Read>(reader: &mut BitReader<R>, options: &WindowOptions) -> io::Result<Option<[usize; 2]>> {
+    let (offset_width, length_width) = field_widths(options);
+    let token = match reader.read_bit()? {
+        Some(false) => reader.read_bits(8)?.map(|byte| [0, byte as usize]),
+        Some(true) => {
+            let offset = reader.read_bits(offset_width)?;
+            let length = reader.read_bits(length_width)?;
+            offset.zip(length).map(|(offset, length)| [length as usize + 1, offset as usize])
+        },
+        None => None,
+    };
+    return Ok(token);
+}
+
+pub fn unpack(coded: &[Byte], options: &WindowOptions) -> Vec<usize> {
+    let mut reader = BitReader::new(coded);
+    let mut values = Vec::new();
+    while let Ok(Some(token)) = read_token(&mut reader, options) {
+        values.extend(token);
+    }
+    return values;
+}
@@ -1,84 +1,306 @@
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use crate::options::{DictionaryOptions, DictionaryPolicy};
+
 const NO_BYTES: usize = 256;
 
+// emitted by the RESET policy when the dictionary fills up
+
+pub const CLEAR_CODE: usize = NO_BYTES;
+
 type Byte = u8;
 
-#[derive(Debug)]
+/* the encoder's trie kept as a single map from (parent code, byte) to the child's code,
+ * so an entry takes a few words instead of an array of 256 pointers */
+
 struct Dictionary {
-    value: usize,
-    next_entries: [Option<Box<Dictionary>>; NO_BYTES],
+    children: HashMap<usize, usize>,
 }
 
 impl Dictionary {
-    fn new(val: usize) -> Self {
+    fn new() -> Self {
         Self {
-            value: val,
-            next_entries: (0..NO_BYTES)
-                .map(|_| None)
-                .collect::<Vec<_>>()
-                .try_into()
-                .expect("Should have turned vec into arr"),
+            children: HashMap::new(),
         }
     }
 
-    fn new_root() -> Self {
-        Self {
-            value: 0,
-            next_entries: (0..NO_BYTES)
-                .map(|val| Some(Box::new(Self::new(val))))
-                .collect::<Vec<_>>()
-                .try_into()
-                .expect("Should have turned vec into arr"),
+    fn key(parent: usize, byte: Byte) -> usize {
+        return (parent << 8) | byte as usize;
+    }
+
+    fn add(&mut self, value: usize, parent: usize, byte: Byte) {
+        self.children.insert(Self::key(parent, byte), value);
+    }
+
+    fn remove(&mut self, parent: usize, byte: Byte) {
+        self.children.remove(&Self::key(parent, byte));
+    }
+
+    fn clear(&mut self) {
+        self.children.clear();
+    }
+
+    // the code of the longest entry starting the (non-empty) bytes, and its length
+
+    fn longest_match(&self, bytes: &[Byte]) -> (usize, usize) {
+        let mut code = bytes[0] as usize;
+        let mut len = 1;
+        while let Some(&next) = bytes.get(len).and_then(|&byte| self.children.get(&Self::key(code, byte))) {
+            code = next;
+            len += 1;
+        }
+        return (code, len);
+    }
+}
+
+/* the codes of the dictionary, assigned identically by the encoder and the decoder */
+
+enum Slot {
+    New(usize),
+    Evicted(usize),
+    Clear,
+    Full,
+}
+
+struct Slots {
+    options: DictionaryOptions,
+    first_code: usize,
+    next_code: usize,
+
+    // every entry is its parent entry followed by a byte; the decoder reconstructs the entries from them
+
+    parents: Vec<(usize, Byte)>,
+
+    // for the LRU policy only the entries which aren't prefixes of other entries can be evicted
+
+    child_counts: Vec<usize>,
+    last_used: Vec<u64>,
+    leaves: BTreeSet<(u64, usize)>,
+    clock: u64,
+}
+
+// the first code of the entries longer than a byte
+
+pub(crate) fn first_code(policy: DictionaryPolicy) -> usize {
+    return match policy {
+        DictionaryPolicy::RESET => CLEAR_CODE + 1,
+        _ => NO_BYTES,
+    };
+}
+
+impl Slots {
+    fn new(options: &DictionaryOptions) -> Self {
+        let first_code = first_code(options.policy);
+        return Self {
+            options: *options,
+            first_code,
+            next_code: first_code,
+            parents: vec![(0, 0); first_code],
+            child_counts: vec![0; first_code],
+            last_used: vec![0; first_code],
+            leaves: BTreeSet::new(),
+            clock: 0,
+        };
+    }
+
+    fn reset(&mut self) {
+        self.next_code = self.first_code;
+        self.parents.truncate(self.first_code);
+        self.child_counts.truncate(self.first_code);
+        self.last_used.truncate(self.first_code);
+        self.leaves.clear();
+    }
+
+    fn is_lru(&self) -> bool {
+        return self.options.policy == DictionaryPolicy::LRU;
+    }
+
+    fn touch(&mut self, code: usize) {
+        if !self.is_lru() || code < self.first_code {
+            return;
+        }
+        self.clock += 1;
+        if self.child_counts[code] == 0 {
+            self.leaves.remove(&(self.last_used[code], code));
+            self.leaves.insert((self.clock, code));
         }
+        self.last_used[code] = self.clock;
     }
 
-    fn add(&mut self, value: usize, pos: usize) {
-        self.next_entries[pos] = Some(Box::new(Self::new(value)));
+    // where the entry extending {prefix} goes; the prefix itself is never evicted
+
+    fn slot(&mut self, prefix: usize) -> Slot {
+        match self.options.max_size {
+            Some(max_size) if self.next_code >= max_size => {},
+            _ => return Slot::New(self.next_code),
+        }
+
+        return match self.options.policy {
+            DictionaryPolicy::FREEZE => Slot::Full,
+            DictionaryPolicy::RESET => Slot::Clear,
+            DictionaryPolicy::LRU => {
+                let victim = self.leaves.iter().find(|(_, code)| *code != prefix).copied();
+                match victim {
+                    Some((last_used, code)) => {
+                        self.leaves.remove(&(last_used, code));
+                        let parent = self.parents[code].0;
+                        self.child_counts[parent] -= 1;
+                        if parent >= self.first_code && self.child_counts[parent] == 0 {
+                            self.leaves.insert((self.last_used[parent], parent));
+                        }
+                        Slot::Evicted(code)
+                    },
+                    None => Slot::Full,
+                }
+            },
+        };
+    }
+
+    fn add(&mut self, code: usize, prefix: usize, byte: Byte) {
+        if code == self.next_code {
+            self.next_code += 1;
+            self.parents.push((prefix, byte));
+        } else {
+            self.parents[code] = (prefix, byte);
+        }
+
+        if self.is_lru() {
+            if code == self.child_counts.len() {
+                self.child_counts.push(0);
+                self.last_used.push(0);
+            }
+            if prefix >= self.first_code && self.child_counts[prefix] == 0 {
+                self.leaves.remove(&(self.last_used[prefix], prefix));
+            }
+            self.child_counts[prefix] += 1;
+            self.clock += 1;
+            self.last_used[code] = self.clock;
+            self.leaves.insert((self.clock, code));
+        }
+    }
+
+    // append the bytes of the entry by walking back its parents
+
+    fn write_entry(&self, mut code: usize, out: &mut Vec<Byte>) {
+        let start = out.len();
+        while code >= self.first_code {
+            let (parent, byte) = self.parents[code];
+            out.push(byte);
+            code = parent;
+        }
+        out.push(code as Byte);
+        out[start..].reverse();
     }
 }
 
 pub fn encode(message: &[Byte]) -> Vec<usize> {
+    return encode_with(message, &DictionaryOptions::default());
+}
+
+pub fn encode_with(message: &[Byte], options: &DictionaryOptions) -> Vec<usize> {
     let mut coded: Vec<usize> = Vec::new();
 
-    let mut root = Dictionary::new_root();
-    let mut curr_prefix = &mut root;
-    let mut next_value = NO_BYTES;
-
-    for byte in message {
-        if curr_prefix.next_entries[*byte as usize].is_none() {
-            coded.push(curr_prefix.value);
-            
-            curr_prefix.add(next_value, *byte as usize);
-            next_value += 1;
-            
-            curr_prefix = &mut root;
+    let mut dictionary = Dictionary::new();
+    let mut slots = Slots::new(options);
+    let mut pos = 0;
+
+    while pos < message.len() {
+        let (prefix, prefix_len) = dictionary.longest_match(&message[pos..]);
+        coded.push(prefix);
+        slots.touch(prefix);
+
+        pos += prefix_len;
+        if pos == message.len() {
+            break;
         }
-        curr_prefix = curr_prefix.next_entries[*byte as usize].as_mut().unwrap();
+
+        // extend the dictionary with the entry followed by the next byte
+
+        let code = match slots.slot(prefix) {
+            Slot::New(code) => code,
+            Slot::Evicted(code) => {
+                let (parent, byte) = slots.parents[code];
+                dictionary.remove(parent, byte);
+                code
+            },
+            Slot::Clear => {
+                coded.push(CLEAR_CODE);
+                dictionary.clear();
+                slots.reset();
+                continue;
+            },
+            Slot::Full => continue,
+        };
+        dictionary.add(code, prefix, message[pos]);
+        slots.add(code, prefix, message[pos]);
     }
-    coded.push(curr_prefix.value); // probably doesn't need 'if'
 
     return coded;
 }
 
 pub fn decode(coded: &[usize]) -> Vec<Byte> {
+    return decode_with(coded, &DictionaryOptions::default());
+}
+
+// decoding stops at the first code absent from the dictionary
+
+pub fn decode_with(coded: &[usize], options: &DictionaryOptions) -> Vec<Byte> {
+    return decode_up_to(coded, options, usize::MAX);
+}
+
+// decoding also stops once the message is longer than {max_len}, so that a corrupted code
+// can't make it grow without bound
+
+pub fn decode_up_to(coded: &[usize], options: &DictionaryOptions, max_len: usize) -> Vec<Byte> {
     let mut decoded: Vec<Byte> = Vec::new();
 
-    let mut dict_bytes: Vec<Vec<Byte>> = (0..NO_BYTES)
-        .map(|byte| vec![byte as u8])
-        .collect();
+    let mut slots = Slots::new(options);
+
+    // the previous code and the position of its bytes in the decoded message
 
-    for index in coded {
-        if dict_bytes.len() > NO_BYTES {
-            let last_symbol = dict_bytes[*index][0].clone();
-            dict_bytes.last_mut()
-                .unwrap()
-                .push(last_symbol);
+    let mut prev: Option<(usize, usize)> = None;
+
+    for &index in coded {
+        if decoded.len() > max_len {
+            break;
+        }
+        if options.policy == DictionaryPolicy::RESET && index == CLEAR_CODE {
+            slots.reset();
+            prev = None;
+            continue;
         }
 
-        decoded.extend(&dict_bytes[*index]);
+        // the encoder has added the previous entry followed by the first byte of this one,
+        // which may be the very entry just added
 
-        dict_bytes.push(dict_bytes[*index].clone());
+        let slot = match prev {
+            Some((prefix, _)) => match slots.slot(prefix) {
+                Slot::New(code) | Slot::Evicted(code) => Some((prefix, code)),
+                Slot::Clear | Slot::Full => None,
+            },
+            None => None,
+        };
+
+        let start = decoded.len();
+        match (slot, prev) {
+            (Some((prefix, code)), Some((_, prev_start))) if code == index => {
+                slots.add(code, prefix, decoded[prev_start]);
+                slots.write_entry(index, &mut decoded);
+            },
+            _ => {
+                if index >= slots.next_code {
+                    break;
+                }
+                slots.write_entry(index, &mut decoded);
+                if let Some((prefix, code)) = slot {
+                    slots.add(code, prefix, decoded[start]);
+                }
+            },
+        }
+
+        slots.touch(index);
+        prev = Some((index, start));
     }
 
     return decoded;
 }
-
@@ -1,52 +1,109 @@
 use dictionary_code::*;
-use entropy::calculate_entropy_from_bytes;
+use dictionary_code::options::{DictionaryAlgorithm, DictionaryOptions, DictionaryPolicy};
+use entropy::accumulator::EntropyAccumulator;
+use entropy::stats::CompressionStats;
+use entropy::symbols::calculate_symbol_entropy;
 use std::fs;
-use std::fs::File;
 use std::error::Error;
 
-fn print_statistics(text: &[u8], args: &[String]) -> Result<(), std::io::Error> {
-    let input_file = File::open(&args[2])?;
-    let input_file_len = input_file.metadata().unwrap().len();
-    println!("input file length = {}", input_file_len);
-    println!("entropy = {}", calculate_entropy_from_bytes(text));
+fn print_statistics(text: &[u8], code: &[u8], stats: &CompressionStats, dictionary: &DictionaryOptions) {
+    print!("{}", stats);
+    let (name, values) = match dictionary.algorithm {
+        DictionaryAlgorithm::LZW => ("dictionary index", lzw::encode_with(text, dictionary)),
+        DictionaryAlgorithm::LZ78 => ("dictionary index", lz78::encode_with(text, dictionary)),
+        DictionaryAlgorithm::LZMW | DictionaryAlgorithm::LZAP => ("dictionary index", lzmw::encode_with(text, dictionary)),
+        DictionaryAlgorithm::LZSS => ("lzss value", lzss::tokens_to_values(&lzss::encode(text, &dictionary.window))),
+    };
+    println!("{} entropy = {}", name, calculate_symbol_entropy(&values));
+    let mut code_statistics = EntropyAccumulator::new();
+    code_statistics.update(code);
+    println!("code entropy = {}", code_statistics.entropy());
+}
 
-    let output_file = File::open(&args[3])?;
-    let output_file_len = output_file.metadata().unwrap().len();
-    println!("output file length = {}", output_file_len);
-    let code = fs::read(&args[3])?;
-    println!("code entropy = {}", calculate_entropy_from_bytes(&code));
+// split the arguments into the positional ones and the options given as --option value;
+// --lzss is short for --algorithm lzss
 
-    let compression_ratio = input_file_len as f64 / output_file_len as f64;
-    println!("compression ratio = {}", compression_ratio);
+fn parse_options(args: &[String]) -> Result<(Vec<String>, DictionaryOptions), Box<dyn Error>> {
+    let mut positional = Vec::new();
+    let mut options = DictionaryOptions::default();
 
-    Ok(())
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--max-size" => {
+                let value = args.next().ok_or("Please specify the maximum dictionary size")?;
+                options.max_size = Some(value.parse()?);
+            },
+            "--policy" => {
+                options.policy = match args.next().map(|policy| policy.as_str()) {
+                    Some("freeze") => DictionaryPolicy::FREEZE,
+                    Some("reset") => DictionaryPolicy::RESET,
+                    Some("lru") => DictionaryPolicy::LRU,
+                    _ => return Err("Please specify a valid dictionary policy: freeze, reset or lru".into()),
+                };
+            },
+            "--algorithm" => {
+                options.algorithm = match args.next().map(|algorithm| algorithm.as_str()) {
+                    Some("lzw") => DictionaryAlgorithm::LZW,
+                    Some("lz78") => DictionaryAlgorithm::LZ78,
+                    Some("lzmw") => DictionaryAlgorithm::LZMW,
+                    Some("lzap") => DictionaryAlgorithm::LZAP,
+                    Some("lzss") => DictionaryAlgorithm::LZSS,
+                    _ => return Err("Please specify a valid dictionary algorithm: lzw, lz78, lzmw, lzap or lzss".into()),
+                };
+            },
+            "--lzss" => options.algorithm = DictionaryAlgorithm::LZSS,
+            "--window" => {
+                let value = args.next().ok_or("Please specify the window size")?;
+                options.window.window_size = value.parse()?;
+            },
+            "--lookahead" => {
+                let value = args.next().ok_or("Please specify the lookahead size")?;
+                options.window.lookahead_size = value.parse()?;
+            },
+            _ => positional.push(arg.clone()),
+        }
+    }
+
+    return Ok((positional, options));
 }
 
 fn main() -> Result<(), Box<dyn Error>>{
-    let args: Vec<String> = std::env::args().collect();
-    if args.len() < 5 {
-        println!("Usage: path/to/programme action <input-file> <output-file> <code-type>");
+    let (mut args, dictionary) = parse_options(&std::env::args().collect::<Vec<String>>())?;
+    let json = args.iter().any(|arg| arg == "--json");
+    args.retain(|arg| arg != "--json");
+    if args.len() < 4 {
+        println!("Usage: path/to/programme action <input-file> <output-file> [<code-type>] [--json] [--max-size N] [--policy freeze|reset|lru] [--algorithm lzw|lz78|lzmw|lzap|lzss] [--lzss] [--window N] [--lookahead N]");
         std::process::exit(1);
     }
     
     let text = fs::read(&args[2])?;
 
-    let code = match args[4].as_str() {
-        "gamma" => CodeType::GAMMA,
-        "delta" => CodeType::DELTA,
-        "omega" => CodeType::OMEGA,
-        "fib" => CodeType::FIB,
+    // the code type is read from the header when decompressing
+
+    let code = match args.get(4).map(|code| code.as_str()) {
+        Some("gamma") => Some(CodeType::GAMMA),
+        Some("delta") => Some(CodeType::DELTA),
+        Some("omega") => Some(CodeType::OMEGA),
+        Some("fib") => Some(CodeType::FIB),
+        Some("variable") => Some(CodeType::VARIABLE_WIDTH),
+        None => None,
         _ => {println!("Invalid code type"); std::process::exit(1);},
     };
 
     match args[1].as_str() {
         "compress" => {
-            let compressed = compress_bytes(&text, &code);
-            fs::write(&args[3], compressed)?;
-            print_statistics(&text, &args)?;
+            let code = code.ok_or("Please specify the code type")?;
+            let (compressed, stats) = compress_bytes_with_stats(&text, &code, &dictionary);
+            fs::write(&args[3], &compressed)?;
+            if json {
+                print!("{}", stats.to_json());
+            } else {
+                print_statistics(&text, &compressed, &stats, &dictionary);
+            }
         },
         "decompress" => {
-            let decompressed = decompress_bytes(&text, &code);
+            let decompressed = decompress_bytes(&text)?;
             fs::write(&args[3], decompressed)?;
         },
         _ => {println!("Invalid action"); std::process::exit(1);},
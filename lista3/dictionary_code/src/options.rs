@@ -0,0 +1,54 @@
+use crate::lzss::WindowOptions;
+
+// what happens once the dictionary reaches its maximum size
+
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(Debug)]
+#[derive(PartialEq)]
+pub enum DictionaryPolicy {
+    FREEZE,
+    RESET,
+    LRU,
+}
+
+// how the dictionary grows, selected in compress_bytes; LZW, LZ78, LZMW, LZAP and LZSS are each in their own module,
+// and LZSS takes the sliding window of the last bytes for its dictionary
+
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(Debug)]
+#[derive(PartialEq)]
+pub enum DictionaryAlgorithm {
+    LZW,
+    LZ78,
+    LZMW,
+    LZAP,
+    LZSS,
+}
+
+// {max_size} counts all the codes, the single bytes and the clear code included;
+// no maximum means the dictionary grows with the message; {window} is used by LZSS only,
+// and {max_size} and {policy} by all the other algorithms
+
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(Debug)]
+#[derive(PartialEq)]
+pub struct DictionaryOptions {
+    pub max_size: Option<usize>,
+    pub policy: DictionaryPolicy,
+    pub algorithm: DictionaryAlgorithm,
+    pub window: WindowOptions,
+}
+
+impl Default for DictionaryOptions {
+    fn default() -> Self {
+        return Self {
+            max_size: None,
+            policy: DictionaryPolicy::FREEZE,
+            algorithm: DictionaryAlgorithm::LZW,
+            window: WindowOptions::default(),
+        };
+    }
+}
@@ -0,0 +1,171 @@
+use crate::lzw::{first_code, CLEAR_CODE};
+use crate::options::{DictionaryOptions, DictionaryPolicy};
+
+/* LZW indices as plain binary numbers whose width follows the size of the dictionary, laid out as in Unix compress:
+ * packed from the least significant bit, with the codes of every width written in groups of 8 (padded with zeros)
+ * which end whenever the width changes or the dictionary is cleared; with the RESET policy and 2^16 entries at most
+ * the output prefixed with the bytes 1F 9D 90 is a valid .Z file */
+
+pub(crate) const MIN_WIDTH: u32 = 9;
+const GROUP_SIZE: usize = 8;
+
+// the padding before the next value and its width, given the values so far
+
+pub(crate) trait Widths {
+    fn next_width(&mut self) -> (u32, u32);
+    fn consume(&mut self, value: usize);
+}
+
+// follows the dictionary of the LZW coder, which grows by an entry before every code but the first one
+// (and the first one after a clear code), until it reaches the maximum size
+
+struct CodeWidths {
+    first_code: usize,
+    max_code: Option<usize>,
+    reset: bool,
+    next_code: usize,
+    started: bool,
+    width: u32,
+    codes_in_width: usize,
+    cleared: bool,
+}
+
+impl CodeWidths {
+    fn new(options: &DictionaryOptions) -> Self {
+        let first_code = first_code(options.policy);
+        return Self {
+            first_code,
+            max_code: options.max_size.map(|max_size| max_size.max(first_code) - 1),
+            reset: options.policy == DictionaryPolicy::RESET,
+            next_code: first_code,
+            started: false,
+            width: MIN_WIDTH,
+            codes_in_width: 0,
+            cleared: false,
+        };
+    }
+}
+
+impl Widths for CodeWidths {
+
+    // the padding ending the group before the next code, and the width of the code; as in compress
+    // the width holds the next free code from before the entry added along with the code, which is the largest code possible
+
+    fn next_width(&mut self) -> (u32, u32) {
+        let largest = match self.max_code {
+            Some(max_code) => self.next_code.min(max_code),
+            None => self.next_code,
+        };
+        let width = (usize::BITS - largest.leading_zeros()).max(MIN_WIDTH);
+
+        let full = self.max_code.is_some_and(|max_code| self.next_code > max_code);
+        if self.started && !full {
+            self.next_code += 1;
+        }
+        self.started = true;
+
+        let mut padding = 0;
+        if width != self.width || self.cleared {
+            padding = ((GROUP_SIZE - self.codes_in_width % GROUP_SIZE) % GROUP_SIZE) as u32 * self.width;
+            self.width = width;
+            self.codes_in_width = 0;
+            self.cleared = false;
+        }
+        self.codes_in_width += 1;
+
+        return (padding, width);
+    }
+
+    fn consume(&mut self, code: usize) {
+        if self.reset && code == CLEAR_CODE {
+            self.next_code = self.first_code;
+            self.started = false;
+            self.cleared = true;
+        }
+    }
+}
+
+pub fn encode(values: &[usize], options: &DictionaryOptions) -> Vec<u8> {
+    return encode_with_widths(values, &mut CodeWidths::new(options));
+}
+
+pub(crate) fn encode_with_widths<W: Widths>(values: &[usize], widths: &mut W) -> Vec<u8> {
+    let mut coded = Vec::new();
+    let mut buffer: u128 = 0;
+    let mut buffered_bits = 0;
+
+    for &value in values {
+        let (padding, width) = widths.next_width();
+
+        // the padding may be longer than the buffer, so it's written byte by byte
+
+        buffered_bits += padding;
+        while buffered_bits >= 8 {
+            coded.push(buffer as u8);
+            buffer >>= 8;
+            buffered_bits -= 8;
+        }
+
+        buffer |= (value as u128) << buffered_bits;
+        buffered_bits += width;
+        while buffered_bits >= 8 {
+            coded.push(buffer as u8);
+            buffer >>= 8;
+            buffered_bits -= 8;
+        }
+        widths.consume(value);
+    }
+
+    if buffered_bits > 0 {
+        coded.push(buffer as u8);
+    }
+    return coded;
+}
+
+// the final padding is shorter than any code, so the decoding stops once fewer bits than the width are left
+
+pub fn decode(coded: &[u8], options: &DictionaryOptions) -> Vec<usize> {
+    return decode_with_widths(coded, &mut CodeWidths::new(options));
+}
+
+pub(crate) fn decode_with_widths<W: Widths>(coded: &[u8], widths: &mut W) -> Vec<usize> {
+    let mut values = Vec::new();
+    let mut buffer: u128 = 0;
+    let mut buffered_bits = 0;
+    let mut bytes = coded.iter();
+
+    loop {
+        let (mut padding, width) = widths.next_width();
+        while padding > 0 {
+            if buffered_bits == 0 {
+                match bytes.next() {
+                    Some(&byte) => {
+                        buffer = byte as u128;
+                        buffered_bits = 8;
+                    },
+                    None => return values,
+                }
+            }
+            let skipped = padding.min(buffered_bits);
+            buffer >>= skipped;
+            buffered_bits -= skipped;
+            padding -= skipped;
+        }
+
+        while buffered_bits < width {
+            match bytes.next() {
+                Some(&byte) => {
+                    buffer |= (byte as u128) << buffered_bits;
+                    buffered_bits += 8;
+                },
+                None => return values,
+            }
+        }
+
+        let value = (buffer & ((1 << width) - 1)) as usize;
+        buffer >>= width;
+        buffered_bits -= width;
+        values.push(value);
+        widths.consume(value);
+    }
+}
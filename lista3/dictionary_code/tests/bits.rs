@@ -0,0 +1,107 @@
+use std::io::Read;
+use dictionary_code::bits::{BitReader, BitWriter};
+use dictionary_code::elias_code;
+use dictionary_code::fibonacci_code;
+
+// hands out a single byte per read, as a slow stream would
+
+struct ByteByByte<'a>(&'a [u8]);
+
+impl Read for ByteByByte<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self.0.split_first() {
+            Some((&byte, rest)) if !buf.is_empty() => {
+                buf[0] = byte;
+                self.0 = rest;
+                Ok(1)
+            },
+            _ => Ok(0),
+        }
+    }
+}
+
+fn fields() -> Vec<(u64, u32)> {
+    let mut seed: u64 = 17;
+    return (0..5000).map(|i| {
+        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        let width = (i % 65) as u32;
+        let value = if width == 64 { seed } else { seed & ((1 << width) - 1) };
+        (value, width)
+    }).collect();
+}
+
+#[test]
+fn packs_most_significant_bit_first() {
+    let mut writer = BitWriter::new(Vec::new());
+    writer.write_bit(true).unwrap();
+    writer.write_bits(0b0110, 4).unwrap();
+    writer.write_bits(0x1FF, 9).unwrap();
+    assert_eq!(writer.bits_written(), 14);
+    assert_eq!(writer.finish(false).unwrap(), vec![0b1011_0111, 0b1111_1100]);
+
+    let mut writer = BitWriter::new(Vec::new());
+    writer.write_zeros(3).unwrap();
+    assert_eq!(writer.finish(true).unwrap(), vec![0b0001_1111]);
+}
+
+#[test]
+fn round_trip_fields() {
+    let fields = fields();
+    let mut writer = BitWriter::new(Vec::new());
+    for &(value, width) in &fields {
+        writer.write_bits(value, width).unwrap();
+    }
+    let total_bits: u64 = fields.iter().map(|&(_, width)| width as u64).sum();
+    assert_eq!(writer.bits_written(), total_bits);
+    let coded = writer.finish(false).unwrap();
+    assert_eq!(coded.len() as u64, total_bits.div_ceil(8));
+
+    let mut reader = BitReader::new(ByteByByte(&coded));
+    for &(value, width) in &fields {
+        assert_eq!(reader.read_bits(width).unwrap(), Some(value), "{}", width);
+    }
+    assert_eq!(reader.read_bits((8 - total_bits % 8) as u32 % 8 + 1).unwrap(), None);
+}
+
+#[test]
+fn reading_past_the_end() {
+    let mut reader = BitReader::new(&[0xA5u8][..]);
+    assert_eq!(reader.read_bits(3).unwrap(), Some(0b101));
+    assert_eq!(reader.read_bits(6).unwrap(), None);
+    assert_eq!(reader.read_bits(5).unwrap(), Some(0b00101));
+    assert_eq!(reader.read_bit().unwrap(), None);
+}
+
+#[test]
+fn codes_stream_one_value_at_a_time() {
+    let values: Vec<usize> = (0..3000).map(|i| i * i * 7919 % 100003).collect();
+
+    let mut writer = BitWriter::new(Vec::new());
+    for &value in &values {
+        elias_code::delta_encode_one(value, &mut writer).unwrap();
+    }
+    let coded = writer.finish(false).unwrap();
+    assert_eq!(coded, elias_code::delta_encode(&values));
+
+    let mut reader = BitReader::new(ByteByByte(&coded));
+    for &value in &values {
+        assert_eq!(elias_code::delta_decode_one(&mut reader).unwrap(), Some(value));
+    }
+    assert_eq!(elias_code::delta_decode_one(&mut reader).unwrap(), None);
+
+    let mut fib_table = vec![1, 2];
+    let coded = fibonacci_code::fib_encode(&values);
+    let mut reader = BitReader::new(ByteByByte(&coded));
+    for &value in &values {
+        assert_eq!(fibonacci_code::fib_decode_one(&mut reader, &mut fib_table).unwrap(), Some(value));
+    }
+}
+
+#[test]
+fn codes_round_trip() {
+    let values: Vec<usize> = (0..2000).map(|i| (i * 2654435761usize) % (1 << (i % 40))).collect();
+    assert_eq!(elias_code::gamma_decode(&elias_code::gamma_encode(&values)), values);
+    assert_eq!(elias_code::delta_decode(&elias_code::delta_encode(&values)), values);
+    assert_eq!(elias_code::omega_decode(&elias_code::omega_encode(&values)), values);
+    assert_eq!(fibonacci_code::fib_decode(&fibonacci_code::fib_encode(&values)), values);
+}
@@ -0,0 +1,27 @@
+// every test crate uses only some of the helpers
+
+#![allow(dead_code)]
+
+// the same pseudo-random text on every run
+
+pub fn pseudo_random_text(len: usize, alphabet: &[u8]) -> Vec<u8> {
+    let mut seed: u32 = 7;
+    return (0..len).map(|_| {
+        seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+        alphabet[(seed >> 16) as usize % alphabet.len()]
+    }).collect();
+}
+
+pub fn texts() -> Vec<Vec<u8>> {
+    return vec![
+        vec![],
+        b"a".to_vec(),
+        b"ab".to_vec(),
+        b"abababababababababa".to_vec(),
+        vec![b'x'; 3000],
+        pseudo_random_text(20000, b"ab"),
+        pseudo_random_text(20000, b"abcdefgh"),
+        (0..=255).cycle().take(5000).collect(),
+        "the quick brown fox jumps over the lazy dog; ".repeat(200).into_bytes(),
+    ];
+}
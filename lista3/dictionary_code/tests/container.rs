@@ -0,0 +1,210 @@
+mod common;
+
+use dictionary_code::*;
+use dictionary_code::lzss::{WindowOptions, MAX_WINDOW_SIZE, MIN_MATCH};
+use dictionary_code::options::{DictionaryAlgorithm, DictionaryOptions, DictionaryPolicy};
+use common::pseudo_random_text;
+
+const CODES: [CodeType; 5] = [CodeType::GAMMA, CodeType::DELTA, CodeType::OMEGA, CodeType::FIB, CodeType::VARIABLE_WIDTH];
+
+#[test]
+fn header_round_trip() {
+    let text = pseudo_random_text(5000, b"abcdefgh");
+    for code in CODES {
+        for algorithm in [DictionaryAlgorithm::LZW, DictionaryAlgorithm::LZ78, DictionaryAlgorithm::LZMW, DictionaryAlgorithm::LZAP, DictionaryAlgorithm::LZSS] {
+            for (max_size, policy) in [(None, DictionaryPolicy::FREEZE), (Some(300), DictionaryPolicy::RESET), (Some(1 << 20), DictionaryPolicy::LRU)] {
+                let options = DictionaryOptions{max_size, policy, algorithm, ..Default::default()};
+                let compressed = compress_bytes(&text, &code, &options);
+
+                // LZSS stores neither the policy nor the maximum size, which it doesn't use
+
+                let stored = match algorithm {
+                    DictionaryAlgorithm::LZSS => DictionaryOptions{algorithm, ..Default::default()},
+                    _ => options,
+                };
+                let header = Header::read(&mut &compressed[..]).unwrap();
+                assert_eq!(header, Header{code, dictionary: stored, message_len: 5000, checksum: crc32fast::hash(&text)});
+
+                // the code type and the options aren't needed to decompress
+
+                assert_eq!(decompress_bytes(&compressed).unwrap(), text, "{:?} {:?}", code, options);
+            }
+        }
+    }
+}
+
+#[test]
+fn header_precedes_the_raw_code() {
+    let options = DictionaryOptions::default();
+    let compressed = compress_bytes(b"abababa", &CodeType::VARIABLE_WIDTH, &options);
+    let raw = compress_raw_bytes(b"abababa", &CodeType::VARIABLE_WIDTH, &options);
+
+    // magic, version, code, algorithm, policy, no maximum, length, checksum
+
+    assert_eq!(&compressed[..4], b"LZWC");
+    assert_eq!(&compressed[4..10], &[1, 4, 0, 0, 0, 7]);
+    assert_eq!(&compressed[14..], &raw[..]);
+    assert_eq!(decompress_raw_bytes(&raw, &CodeType::VARIABLE_WIDTH, &options).unwrap(), b"abababa");
+}
+
+#[test]
+fn header_describes_the_lzss_window() {
+    let window = WindowOptions{window_size: 300, lookahead_size: 10, max_chain: 8};
+    let options = DictionaryOptions{algorithm: DictionaryAlgorithm::LZSS, window, ..Default::default()};
+    let compressed = compress_bytes(b"abababa", &CodeType::VARIABLE_WIDTH, &options);
+    let raw = compress_raw_bytes(b"abababa", &CodeType::VARIABLE_WIDTH, &options);
+
+    // magic, version, code, algorithm, window, lookahead, length, checksum
+
+    assert_eq!(&compressed[4..11], &[1, 4, 4, 0xAC, 0x02, 10, 7]);
+    assert_eq!(&compressed[15..], &raw[..]);
+
+    // the length of the hash chains isn't needed to decompress
+
+    let header = Header::read(&mut &compressed[..]).unwrap();
+    assert_eq!(header.dictionary.window, WindowOptions{max_chain: WindowOptions::default().max_chain, ..window});
+    assert_eq!(decompress_bytes(&compressed).unwrap(), b"abababa");
+}
+
+#[test]
+fn rejects_mismatched_window() {
+    let text = pseudo_random_text(5000, b"abcdefgh");
+    let options = DictionaryOptions{algorithm: DictionaryAlgorithm::LZSS, window: WindowOptions{window_size: 100, lookahead_size: 18, max_chain: 32}, ..Default::default()};
+    let compressed = compress_bytes(&text, &CodeType::VARIABLE_WIDTH, &options);
+
+    // a window and a lookahead of other widths in the fixed-width layout
+
+    for (pos, size) in [(7, 50), (8, 40)] {
+        let mut other_window = compressed.clone();
+        other_window[pos] = size;
+        assert!(matches!(
+            decompress_bytes(&other_window),
+            Err(DictionaryError::CorruptedCode) | Err(DictionaryError::LengthMismatch{..}) | Err(DictionaryError::ChecksumMismatch{..}),
+        ), "{} {}", pos, size);
+    }
+}
+
+#[test]
+fn rejects_invalid_window() {
+    let compressed = compress_bytes(b"abababa", &CodeType::GAMMA, &DictionaryOptions{algorithm: DictionaryAlgorithm::LZSS, ..Default::default()});
+    let header = Header::read(&mut &compressed[..]).unwrap();
+    let invalid = [
+        WindowOptions{window_size: 0, ..header.dictionary.window},
+        WindowOptions{lookahead_size: MIN_MATCH - 1, ..header.dictionary.window},
+        WindowOptions{window_size: MAX_WINDOW_SIZE + 1, ..header.dictionary.window},
+        WindowOptions{lookahead_size: usize::MAX, ..header.dictionary.window},
+    ];
+
+    // the sizes are written as they are, bypassing Header::write, in place of the default ones
+    // taking 3 and 2 bytes
+
+    for window in invalid {
+        let mut other_window = compressed[..7].to_vec();
+        for size in [window.window_size, window.lookahead_size] {
+            let mut size = size as u64;
+            while size >= 0x80 {
+                other_window.push((size as u8 & 0x7F) | 0x80);
+                size >>= 7;
+            }
+            other_window.push(size as u8);
+        }
+        other_window.extend(&compressed[12..]);
+        assert!(matches!(decompress_bytes(&other_window), Err(DictionaryError::BadHeader(HeaderError::InvalidWindow))), "{:?}", window);
+    }
+}
+
+#[test]
+fn decoding_stops_after_the_message_length() {
+    let text = vec![b'x'; 100000];
+    for algorithm in [DictionaryAlgorithm::LZW, DictionaryAlgorithm::LZ78, DictionaryAlgorithm::LZMW, DictionaryAlgorithm::LZAP, DictionaryAlgorithm::LZSS] {
+        let options = DictionaryOptions{algorithm, ..Default::default()};
+        let compressed = compress_bytes(&text, &CodeType::GAMMA, &options);
+
+        // the same code behind a header promising a much shorter message
+
+        let mut header = Header::read(&mut &compressed[..]).unwrap();
+        let mut header_bytes = Vec::new();
+        header.write(&mut header_bytes);
+        header.message_len = 10;
+        let mut shortened = Vec::new();
+        header.write(&mut shortened);
+        shortened.extend(&compressed[header_bytes.len()..]);
+
+        match decompress_bytes(&shortened) {
+            Err(DictionaryError::LengthMismatch{expected: 10, actual}) => assert!(actual < 1000, "{:?} {}", algorithm, actual),
+            result => panic!("{:?} {:?}", algorithm, result),
+        }
+    }
+}
+
+#[test]
+fn empty_message() {
+    for code in CODES {
+        let compressed = compress_bytes(b"", &code, &DictionaryOptions::default());
+        assert_eq!(decompress_bytes(&compressed).unwrap(), b"");
+    }
+}
+
+#[test]
+fn rejects_other_files() {
+    let compressed = compress_bytes(b"the quick brown fox", &CodeType::FIB, &DictionaryOptions::default());
+
+    let mut bad_magic = compressed.clone();
+    bad_magic[0] = b'X';
+    assert!(matches!(decompress_bytes(&bad_magic), Err(DictionaryError::BadHeader(HeaderError::BadMagic))));
+
+    let mut bad_version = compressed.clone();
+    bad_version[4] = 2;
+    assert!(matches!(decompress_bytes(&bad_version), Err(DictionaryError::BadHeader(HeaderError::UnsupportedVersion(2)))));
+
+    let mut bad_code = compressed.clone();
+    bad_code[5] = 9;
+    assert!(matches!(decompress_bytes(&bad_code), Err(DictionaryError::BadHeader(HeaderError::UnknownCode(9)))));
+
+    let mut bad_algorithm = compressed.clone();
+    bad_algorithm[6] = 9;
+    assert!(matches!(decompress_bytes(&bad_algorithm), Err(DictionaryError::BadHeader(HeaderError::UnknownAlgorithm(9)))));
+
+    let mut bad_policy = compressed.clone();
+    bad_policy[7] = 9;
+    assert!(matches!(decompress_bytes(&bad_policy), Err(DictionaryError::BadHeader(HeaderError::UnknownPolicy(9)))));
+
+    for len in 0..14 {
+        assert!(matches!(decompress_bytes(&compressed[..len]), Err(DictionaryError::TruncatedInput)), "{}", len);
+    }
+}
+
+#[test]
+fn rejects_mismatched_code() {
+    let text = pseudo_random_text(5000, b"abcdefgh");
+    let compressed = compress_bytes(&text, &CodeType::GAMMA, &DictionaryOptions::default());
+
+    // the same code read as another one decodes to garbage, which the length or the checksum catches
+
+    for id in 1..5 {
+        let mut other_code = compressed.clone();
+        other_code[5] = id;
+        assert!(matches!(
+            decompress_bytes(&other_code),
+            Err(DictionaryError::LengthMismatch{..}) | Err(DictionaryError::ChecksumMismatch{..}),
+        ));
+    }
+}
+
+#[test]
+fn rejects_corrupted_code() {
+    let text = pseudo_random_text(5000, b"abcdefgh");
+    for code in CODES {
+        let compressed = compress_bytes(&text, &code, &DictionaryOptions::default());
+        let mut corrupted = compressed.clone();
+        let middle = corrupted.len() / 2;
+        corrupted[middle] ^= 0x10;
+        assert!(matches!(
+            decompress_bytes(&corrupted),
+            Err(DictionaryError::LengthMismatch{..}) | Err(DictionaryError::ChecksumMismatch{..}),
+        ), "{:?}", code);
+
+        let truncated = &compressed[..(compressed.len() - 10)];
+        assert!(matches!(decompress_bytes(truncated), Err(DictionaryError::LengthMismatch{..})), "{:?}", code);
+    }
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use dictionary_code::*;
+use dictionary_code::lzw::*;
+use common::{pseudo_random_text, texts};
+
+#[test]
+fn round_trip_bounded_dictionaries() {
+    for policy in [DictionaryPolicy::FREEZE, DictionaryPolicy::RESET, DictionaryPolicy::LRU] {
+        for max_size in [None, Some(258), Some(260), Some(300), Some(512), Some(4096)] {
+            let options = DictionaryOptions{max_size, policy, ..Default::default()};
+            for text in texts() {
+                let coded = encode_with(&text, &options);
+                assert_eq!(decode_with(&coded, &options), text, "{:?}", options);
+            }
+        }
+    }
+}
+
+#[test]
+fn codes_stay_below_the_maximum() {
+    let text = pseudo_random_text(20000, b"abcdefgh");
+    for policy in [DictionaryPolicy::FREEZE, DictionaryPolicy::RESET, DictionaryPolicy::LRU] {
+        let options = DictionaryOptions{max_size: Some(400), policy, ..Default::default()};
+        assert!(encode_with(&text, &options).iter().all(|&code| code < 400));
+    }
+}
+
+#[test]
+fn reset_emits_clear_codes() {
+    let text = pseudo_random_text(20000, b"abcdefgh");
+    let options = DictionaryOptions{max_size: Some(300), policy: DictionaryPolicy::RESET, ..Default::default()};
+    let coded = encode_with(&text, &options);
+    assert!(coded.contains(&CLEAR_CODE));
+
+    let unbounded = encode(&text);
+    assert!(!encode_with(&text, &DictionaryOptions{max_size: None, policy: DictionaryPolicy::RESET, ..Default::default()}).contains(&CLEAR_CODE));
+    assert!(unbounded.iter().any(|&code| code >= 300));
+}
+
+#[test]
+fn unbounded_dictionary_is_unchanged() {
+    let options = DictionaryOptions{max_size: None, policy: DictionaryPolicy::LRU, ..Default::default()};
+    assert_eq!(encode_with(b"abababa", &options), vec![97, 98, 256, 258]);
+    assert_eq!(encode(b"abababa"), vec![97, 98, 256, 258]);
+}
+
+#[test]
+fn bounded_compression_round_trip() {
+    let text = "the quick brown fox jumps over the lazy dog; ".repeat(300).into_bytes();
+    for code in [CodeType::GAMMA, CodeType::DELTA, CodeType::OMEGA, CodeType::FIB] {
+        for policy in [DictionaryPolicy::FREEZE, DictionaryPolicy::RESET, DictionaryPolicy::LRU] {
+            let options = DictionaryOptions{max_size: Some(1024), policy, ..Default::default()};
+            let compressed = compress_bytes(&text, &code, &options);
+            assert_eq!(decompress_bytes(&compressed).unwrap(), text);
+        }
+    }
+}
+
+#[test]
+fn variable_width_layout() {
+
+    // 9-bit codes packed from the least significant bit
+
+    let options = DictionaryOptions::default();
+    let coded = variable_width::encode(&[97, 98, 256, 258], &options);
+    assert_eq!(coded, vec![0x61, 0xC4, 0x00, 0x14, 0x08]);
+    assert_eq!(variable_width::decode(&coded, &options), vec![97, 98, 256, 258]);
+}
+
+#[test]
+fn variable_width_grows_with_the_dictionary() {
+    let text = pseudo_random_text(50000, b"abcdefgh");
+    for policy in [DictionaryPolicy::FREEZE, DictionaryPolicy::RESET, DictionaryPolicy::LRU] {
+        for max_size in [None, Some(256), Some(258), Some(512), Some(513), Some(4096)] {
+            let options = DictionaryOptions{max_size, policy, ..Default::default()};
+            let codes = encode_with(&text, &options);
+            let coded = variable_width::encode(&codes, &options);
+            assert_eq!(variable_width::decode(&coded, &options), codes, "{:?}", options);
+
+            let compressed = compress_bytes(&text, &CodeType::VARIABLE_WIDTH, &options);
+            assert_eq!(decompress_bytes(&compressed).unwrap(), text);
+        }
+    }
+
+    // with 4096 entries at most every code takes between 9 and 12 bits
+
+    let options = DictionaryOptions{max_size: Some(4096), policy: DictionaryPolicy::FREEZE, ..Default::default()};
+    let codes = encode_with(&text, &options);
+    let coded = variable_width::encode(&codes, &options);
+    assert!(coded.len() * 8 <= codes.len() * 12 + 7);
+    assert!(coded.len() * 8 > codes.len() * 11);
+}
@@ -0,0 +1,63 @@
+mod common;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use dictionary_code::*;
+use common::pseudo_random_text;
+
+/* the trie of a full dictionary under LRU stays as large as its phrases, measured by counting the allocations;
+ * a single test, so that nothing else allocates meanwhile */
+
+struct CountingAllocator;
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let current = CURRENT.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+        PEAK.fetch_max(current, Ordering::Relaxed);
+        return unsafe { System.alloc(layout) };
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+        unsafe { System.dealloc(ptr, layout) };
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+// the peak of the memory allocated by {f} on top of what was allocated before
+
+fn peak<T, F: FnOnce() -> T>(f: F) -> (T, usize) {
+    let before = CURRENT.load(Ordering::Relaxed);
+    PEAK.store(before, Ordering::Relaxed);
+    let result = f();
+    return (result, PEAK.load(Ordering::Relaxed) - before);
+}
+
+#[test]
+fn evicted_phrases_leave_the_trie() {
+    let bytes: Vec<u8> = (0..=255).collect();
+    let text = pseudo_random_text(1 << 18, &bytes);
+
+    for algorithm in [DictionaryAlgorithm::LZ78, DictionaryAlgorithm::LZMW, DictionaryAlgorithm::LZAP] {
+        let options = DictionaryOptions{max_size: Some(1024), policy: DictionaryPolicy::LRU, algorithm, ..Default::default()};
+        let coded = match algorithm {
+            DictionaryAlgorithm::LZ78 => lz78::encode_with(&text, &options),
+            _ => lzmw::encode_with(&text, &options),
+        };
+
+        // the decoded message takes at most three times its length while it grows, the trie of 1024 phrases
+        // a fraction of the rest, while a trie keeping every phrase ever added takes megabytes
+
+        let (decoded, decode_peak) = peak(|| match algorithm {
+            DictionaryAlgorithm::LZ78 => lz78::decode_with(&coded, &options),
+            _ => lzmw::decode_with(&coded, &options),
+        });
+        assert_eq!(decoded, text);
+        assert!(decode_peak < 3 * text.len() + (1 << 18), "{:?} {}", algorithm, decode_peak);
+    }
+}
@@ -0,0 +1,130 @@
+mod common;
+
+use dictionary_code::*;
+use dictionary_code::lzss::*;
+use dictionary_code::options::{DictionaryAlgorithm, DictionaryOptions};
+use common::{pseudo_random_text, texts};
+
+fn windows() -> Vec<WindowOptions> {
+    return vec![
+        WindowOptions::default(),
+        WindowOptions{window_size: 1, lookahead_size: 3, max_chain: 4},
+        WindowOptions{window_size: 16, lookahead_size: 5, max_chain: 8},
+        WindowOptions{window_size: 1000, lookahead_size: 18, max_chain: 32},
+        WindowOptions{window_size: 4096, lookahead_size: 18, max_chain: 1},
+    ];
+}
+
+fn lzss_options(window: &WindowOptions) -> DictionaryOptions {
+    return DictionaryOptions{algorithm: DictionaryAlgorithm::LZSS, window: *window, ..Default::default()};
+}
+
+#[test]
+fn round_trip_tokens() {
+    for window in windows() {
+        for text in texts() {
+            let tokens = encode(&text, &window);
+            assert_eq!(decode(&tokens, &window).unwrap(), text, "{:?}", window);
+            assert_eq!(values_to_tokens(&tokens_to_values(&tokens)).unwrap(), tokens);
+        }
+    }
+}
+
+#[test]
+fn round_trip_all_codes() {
+    for code in [CodeType::GAMMA, CodeType::DELTA, CodeType::OMEGA, CodeType::FIB, CodeType::VARIABLE_WIDTH] {
+        for window in windows() {
+            for text in texts() {
+                let compressed = compress_bytes(&text, &code, &lzss_options(&window));
+                assert_eq!(decompress_bytes(&compressed).unwrap(), text, "{:?}", window);
+            }
+        }
+    }
+}
+
+#[test]
+fn matches_respect_the_window() {
+    let text = pseudo_random_text(20000, b"abcd");
+    for window in windows() {
+        for token in encode(&text, &window) {
+            if let Token::Match{offset, length} = token {
+                assert!(offset >= 1 && offset <= window.window_size.max(1));
+                assert!(length >= MIN_MATCH && length <= window.lookahead_size.max(MIN_MATCH));
+            }
+        }
+    }
+}
+
+#[test]
+fn overlapping_match() {
+    let tokens = encode(&[b'x'; 100], &WindowOptions::default());
+    assert_eq!(tokens, vec![Token::Literal(b'x'), Token::Match{offset: 1, length: 99}]);
+    assert_eq!(decode(&tokens, &WindowOptions::default()).unwrap(), vec![b'x'; 100]);
+}
+
+#[test]
+fn rejects_tokens_outside_of_the_window() {
+    let window = WindowOptions{window_size: 16, lookahead_size: 5, max_chain: 8};
+    let text = vec![Token::Literal(b'a'); 20];
+    for bad in [Token::Match{offset: 0, length: 3}, Token::Match{offset: 17, length: 3}, Token::Match{offset: 21, length: 3}, Token::Match{offset: 1, length: 6}] {
+        let tokens = [text.clone(), vec![bad]].concat();
+        assert!(matches!(decode(&tokens, &window), Err(DictionaryError::CorruptedCode)), "{:?}", bad);
+    }
+
+    // values beyond any length, offset or byte
+
+    for values in [[usize::MAX, 0], [1, usize::MAX], [0, 256]] {
+        assert!(matches!(values_to_tokens(&values), Err(DictionaryError::CorruptedCode)), "{:?}", values);
+    }
+}
+
+#[test]
+fn bit_flips_never_blow_up() {
+    let options = lzss_options(&WindowOptions{window_size: 1000, lookahead_size: 18, max_chain: 32});
+    let text = pseudo_random_text(300, b"abcd");
+    for code in [CodeType::GAMMA, CodeType::DELTA, CodeType::OMEGA, CodeType::FIB, CodeType::VARIABLE_WIDTH] {
+        let raw = compress_raw_bytes(&text, &code, &options);
+        for bit in 0..(raw.len() * 8) {
+            let mut corrupted = raw.clone();
+            corrupted[bit / 8] ^= 0x80 >> (bit % 8);
+
+            // every token adds at most the lookahead to the message
+
+            if let Ok(decoded) = decompress_raw_bytes(&corrupted, &code, &options) {
+                assert!(decoded.len() <= corrupted.len() * 8 * options.window.lookahead_size, "{:?} {}", code, bit);
+            }
+        }
+
+        // with the header, the damage is always detected
+
+        let compressed = compress_bytes(&text, &code, &options);
+        for bit in 0..(compressed.len() * 8) {
+            let mut corrupted = compressed.clone();
+            corrupted[bit / 8] ^= 0x80 >> (bit % 8);
+            if let Ok(decoded) = decompress_bytes(&corrupted) {
+                assert_eq!(decoded, text, "{:?} {}", code, bit);
+            }
+        }
+    }
+}
+
+#[test]
+fn fixed_width_layout() {
+    let window = WindowOptions{window_size: 16, lookahead_size: 5, max_chain: 8};
+    let tokens = vec![Token::Literal(b'a'), Token::Literal(b'b'), Token::Match{offset: 2, length: 4}];
+
+    // 0 01100001, 0 01100010, 1 0001 01 (offset 2 - 1, length 4 - 3), padded with zeros
+
+    assert_eq!(pack(&tokens_to_values(&tokens), &window), vec![0x30, 0x98, 0xA2, 0x80]);
+    assert_eq!(values_to_tokens(&unpack(&[0x30, 0x98, 0xA2, 0x80], &window)).unwrap(), tokens);
+}
+
+#[test]
+fn long_distance_repeat_beats_lzw() {
+    let block = pseudo_random_text(4000, b"abcdefghijklmnopqrstuvwxyz");
+    let text = [block.clone(), block.clone(), block].concat();
+
+    let lzss = compress_bytes(&text, &CodeType::VARIABLE_WIDTH, &lzss_options(&WindowOptions::default()));
+    let lzw = compress_bytes(&text, &CodeType::VARIABLE_WIDTH, &DictionaryOptions::default());
+    assert!(lzss.len() < lzw.len(), "{} {}", lzss.len(), lzw.len());
+}
@@ -0,0 +1,115 @@
+use dictionary_code::bits::{BitReader, BitWriter};
+use dictionary_code::elias_code::*;
+use dictionary_code::fibonacci_code::*;
+
+// the values around every power of two, the largest ones included
+
+fn boundary_values() -> Vec<usize> {
+    let mut values: Vec<usize> = (0..usize::BITS)
+        .flat_map(|k| {
+            let power = 1usize << k;
+            [power.saturating_sub(2), power - 1, power, power.saturating_add(1)]
+        })
+        .chain([usize::MAX - 2, usize::MAX - 1, usize::MAX])
+        .collect();
+    values.sort();
+    values.dedup();
+    return values;
+}
+
+// bit lengths of the codewords of {value}, from the bit length of {value} + 1
+
+fn bit_length(n: u128) -> u64 {
+    return (u128::BITS - n.leading_zeros()) as u64;
+}
+
+fn gamma_length(value: usize) -> u64 {
+    return 2 * bit_length(value as u128 + 1) - 1;
+}
+
+fn delta_length(value: usize) -> u64 {
+    let len = bit_length(value as u128 + 1) - 1;
+    return gamma_length(len as usize) + len;
+}
+
+fn omega_length(value: usize) -> u64 {
+    let mut n = value as u128 + 1;
+    let mut length = 1;
+    while n > 1 {
+        length += bit_length(n);
+        n = bit_length(n) as u128 - 1;
+    }
+    return length;
+}
+
+fn written_bits<F: FnOnce(&mut BitWriter<Vec<u8>>)>(encode_one: F) -> u64 {
+    let mut writer = BitWriter::new(Vec::new());
+    encode_one(&mut writer);
+    return writer.bits_written();
+}
+
+#[test]
+fn exact_lengths_around_powers_of_two() {
+    for value in boundary_values() {
+        assert_eq!(written_bits(|writer| gamma_encode_one(value, writer).unwrap()), gamma_length(value), "gamma {}", value);
+        assert_eq!(written_bits(|writer| delta_encode_one(value, writer).unwrap()), delta_length(value), "delta {}", value);
+        assert_eq!(written_bits(|writer| omega_encode_one(value, writer).unwrap()), omega_length(value), "omega {}", value);
+    }
+}
+
+#[test]
+fn single_values_around_powers_of_two() {
+    for value in boundary_values() {
+        assert_eq!(gamma_decode(&gamma_encode(&[value])), vec![value], "gamma {}", value);
+        assert_eq!(delta_decode(&delta_encode(&[value])), vec![value], "delta {}", value);
+        assert_eq!(omega_decode(&omega_encode(&[value])), vec![value], "omega {}", value);
+        assert_eq!(fib_decode(&fib_encode(&[value])), vec![value], "fib {}", value);
+    }
+}
+
+#[test]
+fn streams_around_powers_of_two() {
+    let values = boundary_values();
+    assert_eq!(gamma_decode(&gamma_encode(&values)), values);
+    assert_eq!(delta_decode(&delta_encode(&values)), values);
+    assert_eq!(omega_decode(&omega_encode(&values)), values);
+    assert_eq!(fib_decode(&fib_encode(&values)), values);
+
+    let reversed: Vec<usize> = values.iter().rev().copied().collect();
+    assert_eq!(fib_decode(&fib_encode(&reversed)), reversed);
+}
+
+#[test]
+fn largest_value() {
+
+    // 2^64 takes 64 zeros and 65 bits
+
+    let mut expected = vec![0; 8];
+    expected.extend([0x80, 0, 0, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(gamma_encode(&[usize::MAX]), expected);
+
+    // the Fibonacci code of 2^64 ends with the largest Fibonacci number below 2^64
+
+    let mut writer = BitWriter::new(Vec::new());
+    fib_encode_one(usize::MAX, &mut vec![1, 2], &mut writer).unwrap();
+    assert_eq!(writer.bits_written(), 93);
+}
+
+#[test]
+fn codewords_beyond_the_largest_value() {
+
+    // 2^64 + 1 in gamma and 2^65 - 1 in delta
+
+    let mut writer = BitWriter::new(Vec::new());
+    writer.write_zeros(64).unwrap();
+    writer.write_bit(true).unwrap();
+    writer.write_bits(1, 64).unwrap();
+    let coded = writer.finish(false).unwrap();
+    assert_eq!(gamma_decode_one(&mut BitReader::new(&coded[..])).unwrap(), None);
+
+    let mut writer = BitWriter::new(Vec::new());
+    gamma_encode_one(64, &mut writer).unwrap();
+    writer.write_bits(u64::MAX, 64).unwrap();
+    let coded = writer.finish(false).unwrap();
+    assert_eq!(delta_decode_one(&mut BitReader::new(&coded[..])).unwrap(), None);
+}
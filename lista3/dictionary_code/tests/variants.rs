@@ -0,0 +1,117 @@
+mod common;
+
+use dictionary_code::*;
+use common::{pseudo_random_text, texts};
+
+const VARIANTS: [DictionaryAlgorithm; 3] = [DictionaryAlgorithm::LZ78, DictionaryAlgorithm::LZMW, DictionaryAlgorithm::LZAP];
+
+fn encode_variant(text: &[u8], options: &DictionaryOptions) -> Vec<usize> {
+    return match options.algorithm {
+        DictionaryAlgorithm::LZ78 => lz78::encode_with(text, options),
+        _ => lzmw::encode_with(text, options),
+    };
+}
+
+fn decode_variant(coded: &[usize], options: &DictionaryOptions) -> Vec<u8> {
+    return match options.algorithm {
+        DictionaryAlgorithm::LZ78 => lz78::decode_with(coded, options),
+        _ => lzmw::decode_with(coded, options),
+    };
+}
+
+#[test]
+fn round_trip_bounded_dictionaries() {
+    for algorithm in VARIANTS {
+        for policy in [DictionaryPolicy::FREEZE, DictionaryPolicy::RESET, DictionaryPolicy::LRU] {
+            for max_size in [None, Some(1), Some(2), Some(256), Some(260), Some(512), Some(4096)] {
+                let options = DictionaryOptions{max_size, policy, algorithm, ..Default::default()};
+                for text in texts() {
+                    let coded = encode_variant(&text, &options);
+                    assert_eq!(decode_variant(&coded, &options), text, "{:?}", options);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn round_trip_all_codes() {
+    let text = "the quick brown fox jumps over the lazy dog; ".repeat(300).into_bytes();
+    for algorithm in VARIANTS {
+        for code in [CodeType::GAMMA, CodeType::DELTA, CodeType::OMEGA, CodeType::FIB, CodeType::VARIABLE_WIDTH] {
+            for max_size in [None, Some(300), Some(1024)] {
+                let options = DictionaryOptions{max_size, policy: DictionaryPolicy::RESET, algorithm, ..Default::default()};
+                let compressed = compress_bytes(&text, &code, &options);
+                assert_eq!(decompress_bytes(&compressed).unwrap(), text, "{:?}", options);
+            }
+        }
+    }
+}
+
+#[test]
+fn codes_stay_below_the_maximum() {
+    let text = pseudo_random_text(20000, b"abcdefgh");
+    for algorithm in VARIANTS {
+        for policy in [DictionaryPolicy::FREEZE, DictionaryPolicy::RESET, DictionaryPolicy::LRU] {
+            let options = DictionaryOptions{max_size: Some(400), policy, algorithm, ..Default::default()};
+            let coded = encode_variant(&text, &options);
+            match algorithm {
+                DictionaryAlgorithm::LZ78 => assert!(coded.iter().step_by(2).all(|&code| code < 400)),
+                _ => assert!(coded.iter().all(|&code| code < 400)),
+            }
+        }
+    }
+}
+
+#[test]
+fn lru_replaces_phrases_of_a_full_dictionary() {
+
+    // the phrases of the first half are useless for the second one, which a frozen dictionary can't learn
+
+    let text = [pseudo_random_text(20000, b"abcdefgh"), pseudo_random_text(20000, b"stuvwxyz")].concat();
+    for algorithm in VARIANTS {
+        let freeze = DictionaryOptions{max_size: Some(1024), policy: DictionaryPolicy::FREEZE, algorithm, ..Default::default()};
+        let lru = DictionaryOptions{max_size: Some(1024), policy: DictionaryPolicy::LRU, algorithm, ..Default::default()};
+        let frozen = compress_bytes(&text, &CodeType::VARIABLE_WIDTH, &freeze);
+        let replaced = compress_bytes(&text, &CodeType::VARIABLE_WIDTH, &lru);
+        assert!(replaced.len() < frozen.len(), "{:?} {} {}", algorithm, replaced.len(), frozen.len());
+        assert_eq!(decompress_bytes(&replaced).unwrap(), text);
+    }
+}
+
+#[test]
+fn lz78_pairs() {
+    let options = DictionaryOptions{algorithm: DictionaryAlgorithm::LZ78, ..Default::default()};
+
+    // a, b, ab, aba, then the phrase ab without its byte
+
+    let coded = lz78::encode_with(b"abababaab", &options);
+    assert_eq!(coded, vec![0, 97, 0, 98, 1, 98, 3, 97, 3]);
+    assert_eq!(lz78::decode_with(&coded, &options), b"abababaab");
+}
+
+#[test]
+fn lzmw_and_lzap_phrases() {
+
+    // LZMW adds ab, bc, cab, abcab, cabcab; LZAP adds ab, bc, ca, cab, abc, abca, abcab, ...
+
+    let lzmw = DictionaryOptions{algorithm: DictionaryAlgorithm::LZMW, ..Default::default()};
+    let coded = lzmw::encode_with(b"abcabcabcabc", &lzmw);
+    assert_eq!(coded, vec![97, 98, 99, 256, 258, 258, 99]);
+    assert_eq!(lzmw::decode_with(&coded, &lzmw), b"abcabcabcabc");
+
+    let lzap = DictionaryOptions{algorithm: DictionaryAlgorithm::LZAP, ..Default::default()};
+    let coded = lzmw::encode_with(b"abcabcabcabc", &lzap);
+    assert_eq!(coded, vec![97, 98, 99, 256, 259, 259, 99]);
+    assert_eq!(lzmw::decode_with(&coded, &lzap), b"abcabcabcabc");
+}
+
+#[test]
+fn variants_grow_the_dictionary_faster_than_lz78() {
+    let text = "the quick brown fox jumps over the lazy dog; ".repeat(300).into_bytes();
+    let lengths: Vec<usize> = VARIANTS
+        .iter()
+        .map(|&algorithm| compress_bytes(&text, &CodeType::VARIABLE_WIDTH, &DictionaryOptions{algorithm, ..Default::default()}).len())
+        .collect();
+    assert!(lengths[1] < lengths[0] && lengths[2] < lengths[0], "{:?}", lengths);
+}
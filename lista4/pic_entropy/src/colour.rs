@@ -1,6 +1,7 @@
 use std::ops::Add;
 use std::ops::Div;
 use std::ops::Sub;
+use entropy::accumulator::EntropyAccumulator;
 
 pub enum Hue {
     BLUE,
@@ -98,3 +99,36 @@ pub fn extract_colour(v: &Vec<Colour>, hue: &Hue) -> Vec<u8> {
         Hue::RED => extract_red(v),
     };
 }
+
+// the byte statistics of every hue, gathered in one pass over the pixels
+
+pub struct ColourStatistics {
+    pub blue: EntropyAccumulator,
+    pub green: EntropyAccumulator,
+    pub red: EntropyAccumulator,
+}
+
+impl ColourStatistics {
+    pub fn from_pixels(v: &Vec<Colour>) -> Self {
+        let mut statistics = Self {
+            blue: EntropyAccumulator::new(),
+            green: EntropyAccumulator::new(),
+            red: EntropyAccumulator::new(),
+        };
+        for col in v {
+            statistics.blue.update(&[col.blue]);
+            statistics.green.update(&[col.green]);
+            statistics.red.update(&[col.red]);
+        }
+        return statistics;
+    }
+
+    // the statistics of all the bytes of the pixels
+
+    pub fn total(&self) -> EntropyAccumulator {
+        let mut total = self.blue.clone();
+        total.merge(&self.green);
+        total.merge(&self.red);
+        return total;
+    }
+}